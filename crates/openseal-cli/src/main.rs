@@ -1,11 +1,11 @@
 use clap::{Parser, Subcommand};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs;
+use std::io::IsTerminal;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use anyhow::{Result, anyhow, Context};
 use std::process::Command;
-use hex;
-use ed25519_dalek::{VerifyingKey, Signature, Verifier};
-use std::convert::TryInto;
 use serde_json;
 
 #[derive(Parser)]
@@ -22,6 +22,11 @@ enum Commands {
         /// Docker image (must include digest: user/api@sha256:...)
         #[arg(short, long)]
         image: String,
+
+        /// OCI layout or `docker save` archive of the image: also record the identity of its
+        /// flattened filesystem, so that auditors can prove which files ship in it
+        #[arg(long)]
+        archive: Option<PathBuf>,
     },
     /// Run the OpenSeal-wrapped container
     Run {
@@ -47,9 +52,128 @@ enum Commands {
         #[arg(long, short)]
         wax: String,
 
-        /// Optional: Expected Root Hash (Image Digest)
+        /// Optional: Expected Root Hash (`sha256:` image digest or `blake3:` project root)
+        #[arg(long)]
+        root_hash: Option<String>,
+
+        /// Check the seal as of this RFC 3339 time instead of now (e.g. for archived responses)
+        #[arg(long)]
+        at: Option<chrono::DateTime<chrono::Utc>>,
+
+        /// Reject seals issued more than this many seconds before the verification time
+        #[arg(long)]
+        max_age: Option<u32>,
+
+        /// Method of the request that was sealed (checked together with --path)
+        #[arg(long, requires = "path")]
+        method: Option<String>,
+
+        /// Path and query of the request that was sealed, e.g. `/api/price?symbol=BTC`
+        #[arg(long, requires = "method")]
+        path: Option<String>,
+
+        /// File holding the request body (empty when omitted)
+        #[arg(long, requires = "path")]
+        body: Option<PathBuf>,
+
+        /// HTTP status the response was received with; the sealed upstream status must match
+        #[arg(long)]
+        status: Option<u16>,
+
+        /// Expected sealed response header, `Name: value` (repeatable); a bare `Name` must be sealed as absent
+        #[arg(long = "header")]
+        headers: Vec<String>,
+    },
+    /// Generate the identity lock manifest (openseal.lock) for a project directory
+    Lock {
+        /// Project directory to scan
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+
+        /// Output file (defaults to <path>/openseal.lock)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// Fail if the existing lock file is out of date instead of rewriting it (for CI)
+        #[arg(long)]
+        check: bool,
+
+        /// Path and content normalization: native, portable (separators, NFC) or portable-text (also CRLF -> LF)
+        #[arg(long, default_value_t = openseal_core::CanonicalProfile::Native)]
+        canonical: openseal_core::CanonicalProfile,
+    },
+    /// Compute the identity of a project directory
+    Identity {
+        /// Project directory to scan
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+
+        /// Compute the identity of a tar / tar.gz / `docker save` archive instead, without extracting it
+        #[arg(long, conflicts_with = "explain")]
+        archive: Option<PathBuf>,
+
+        /// Compute the identity of a commit (hash, branch, tag, ...) of the repository at --path, from its git objects
+        #[arg(long, conflicts_with_all = ["explain", "archive"])]
+        git: Option<String>,
+
+        /// List every path with its status (included, mutable, excluded, ignored, skipped) and the rule behind it
+        #[arg(long)]
+        explain: bool,
+
+        /// Path and content normalization: native, portable (separators, NFC) or portable-text (also CRLF -> LF)
+        #[arg(long, default_value_t = openseal_core::CanonicalProfile::Native)]
+        canonical: openseal_core::CanonicalProfile,
+
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Recompute image digests from an OCI layout directory or a `docker save` tarball (no Docker daemon needed)
+    Image {
+        /// OCI image layout directory or image tarball (optionally gzipped)
+        path: PathBuf,
+
+        /// Fail unless an image in the archive has this digest (registry digest or Image ID)
         #[arg(long)]
         root_hash: Option<String>,
+
+        /// Print the digests as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Prove that a file ships in an image, against its filesystem identity (see `build --archive`)
+    Prove {
+        /// Path of the file inside the image (e.g. /app/server.js)
+        file: String,
+
+        /// OCI layout directory or image tarball (optionally gzipped)
+        #[arg(long)]
+        archive: PathBuf,
+
+        /// Image to use when the archive holds several (registry digest or Image ID)
+        #[arg(long)]
+        digest: Option<String>,
+
+        /// Expected filesystem root hash (defaults to the one recorded in openseal.json, if any)
+        #[arg(long)]
+        root_hash: Option<String>,
+
+        /// Write the inclusion proof to this file
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Compare two identities (each a project directory or an openseal.lock file)
+    Diff {
+        /// Expected side: sealed lock manifest or directory
+        old: PathBuf,
+
+        /// Actual side: lock manifest or live directory (defaults to the current directory)
+        #[arg(default_value = ".")]
+        new: PathBuf,
+
+        /// Print the diff as JSON
+        #[arg(long)]
+        json: bool,
     },
 }
 
@@ -58,16 +182,16 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Build { image } => {
+        Commands::Build { image, archive } => {
             println!("🐳 OpenSeal v1.0.0-alpha.3: Docker-Based Identity Builder");
             println!("   Image: {}", image);
 
             // 1. Extract Digest (support both formats)
-            let digest = if image.contains("@sha256:") {
+            let digest: openseal_core::Digest = if image.contains("@sha256:") {
                 // Registry format: user/api@sha256:abc...
                 image.split('@').nth(1)
                     .ok_or_else(|| anyhow!("Invalid image format"))?
-                    .to_string()
+                    .parse()?
             } else {
                 // Local development: use Image ID
                 let output = Command::new("docker")
@@ -84,15 +208,28 @@ async fn main() -> Result<()> {
                     ));
                 }
 
-                let id = String::from_utf8(output.stdout)?.trim().to_string();
+                let id = String::from_utf8(output.stdout)?.trim().parse()?;
                 println!("   ⚠️  Development mode: Using Image ID instead of digest");
                 id
             };
 
             println!("   ✅ Root Hash: {}", digest);
 
+            // 2. Optional: file-level identity of the flattened image filesystem
+            let filesystem = match &archive {
+                Some(archive) => {
+                    let filesystem = openseal_core::ImageFilesystem::load(archive, Some(&digest.to_string()))
+                        .with_context(|| format!("Failed to flatten {:?} (it must contain {})", archive, digest))?;
+                    let identity = filesystem.identity()?;
+                    println!("   🗂️  Filesystem Root Hash: {} ({} files, {} layers)",
+                        identity.root_hash, identity.file_count, filesystem.image.layer_digests.len());
+                    Some(identity_json(&identity))
+                }
+                None => None,
+            };
+
             // 3. Create openseal.json (v1 format)
-            let openseal_json = serde_json::json!({
+            let mut openseal_json = serde_json::json!({
                 "version": "1.0.0",
                 "image": {
                     "reference": image,
@@ -104,6 +241,9 @@ async fn main() -> Result<()> {
                     "seal_version": "2.0"
                 }
             });
+            if let Some(filesystem) = filesystem {
+                openseal_json["identity"]["filesystem"] = filesystem;
+            }
 
             let json_path = Path::new("openseal.json");
             fs::write(json_path, serde_json::to_string_pretty(&openseal_json)?)?;
@@ -130,21 +270,21 @@ async fn main() -> Result<()> {
             let json_content = fs::read_to_string(json_path)?;
             let json: serde_json::Value = serde_json::from_str(&json_content)?;
             
-            let expected_digest = json["identity"]["root_hash"]
-                .as_str()
-                .ok_or_else(|| anyhow!("Invalid openseal.json"))?;
+            let expected_digest: openseal_core::Digest = serde_json::from_value(json["identity"]["root_hash"].clone())
+                .context("Invalid identity.root_hash in openseal.json")?;
 
-            // 2. Verify image digest matches
-            let actual_digest = if image.contains("@sha256:") {
+            // 2. Verify image digest matches (the attestation the proxy's seals report)
+            let attested_at = chrono::Utc::now();
+            let actual_digest: openseal_core::Digest = if image.contains("@sha256:") {
                 image.split('@').nth(1)
                     .ok_or_else(|| anyhow!("Image must include digest"))?
-                    .to_string()
+                    .parse()?
             } else {
                 // Development mode: get Image ID
                 let output = Command::new("docker")
                     .args(&["inspect", &image, "--format={{.Id}}"])
                     .output()?;
-                String::from_utf8(output.stdout)?.trim().to_string()
+                String::from_utf8(output.stdout)?.trim().parse()?
             };
 
             if expected_digest != actual_digest {
@@ -258,23 +398,13 @@ async fn main() -> Result<()> {
             println!();
 
             // 6. Create ProjectIdentity from openseal.json (v1 format)
-            let root_hash_str = expected_digest;
-            let root_hash_bytes = if root_hash_str.starts_with("sha256:") {
-                hex::decode(&root_hash_str[7..])
-                    .map_err(|e| anyhow!("Invalid digest hex: {}", e))?
-            } else {
-                return Err(anyhow!("Invalid digest format"));
-            };
-
-            let root_hash = blake3::Hash::from_bytes(
-                root_hash_bytes.as_slice().try_into()
-                    .map_err(|_| anyhow!("Invalid hash length"))?
-            );
-
             let project_identity = openseal_core::ProjectIdentity {
-                root_hash,
+                root_hash: expected_digest,
                 file_count: 0, // Docker images don't have file count
                 mutable_files: vec![], // No mutable files in v1 (containers are immutable)
+                scheme: openseal_core::IdentityScheme::CURRENT,
+                preset: None, // The image digest already pins the file set
+                canonical: openseal_core::CanonicalProfile::Native,
             };
 
             // 7. Start Proxy Server (blocking)
@@ -313,18 +443,331 @@ async fn main() -> Result<()> {
             });
 
             // Start the proxy (blocking call)
-            openseal_runtime::run_proxy_server(port, target_url, project_root, project_identity).await?;
+            openseal_runtime::run_proxy_server(port, target_url, project_root, project_identity, attested_at).await?;
+        }
+        Commands::Verify { response, wax, root_hash, at, max_age, method, path, body, status, headers } => {
+            let body = body.map(fs::read).transpose().context("Failed to read request body")?.unwrap_or_default();
+            let mut options = openseal_core::VerifyOptions {
+                max_age: max_age.map(|seconds| chrono::Duration::seconds(seconds.into())),
+                request: method.zip(path)
+                    .map(|(method, path)| openseal_core::RequestDescription::new(&method, &path, &body)),
+                status,
+                headers: headers.iter()
+                    .map(|header| match header.split_once(':') {
+                        Some((name, value)) => (name.trim().to_string(), Some(value.trim().to_string())),
+                        None => (header.trim().to_string(), None),
+                    })
+                    .collect(),
+                ..Default::default()
+            };
+            if let Some(at) = at {
+                options.at = at;
+            }
+            verify_seal(&response, &wax, root_hash.as_deref(), &options)?;
+        }
+        Commands::Lock { path, output, check, canonical } => {
+            write_lock(&path, output, check, canonical)?;
         }
-        Commands::Verify { response, wax, root_hash } => {
-            verify_seal(&response, &wax, root_hash.as_deref())?;
+        Commands::Identity { path, archive, git, explain, canonical, json } => {
+            match (archive, git) {
+                (Some(archive), _) => show_archive_identity(&archive, canonical, json)?,
+                (None, Some(rev)) => show_git_identity(&path, &rev, canonical, json)?,
+                (None, None) => show_identity(&path, explain, canonical, json)?,
+            }
+        }
+        Commands::Image { path, root_hash, json } => {
+            inspect_image(&path, root_hash.as_deref(), json)?;
+        }
+        Commands::Prove { file, archive, digest, root_hash, output } => {
+            prove_image_file(&file, &archive, digest.as_deref(), root_hash, output)?;
+        }
+        Commands::Diff { old, new, json } => {
+            diff_identities(&old, &new, json)?;
         }
     }
 
     Ok(())
 }
 
+/// Writes (or checks) the lock manifest for a project directory
+fn write_lock(project_root: &Path, output: Option<PathBuf>, check: bool, canonical: openseal_core::CanonicalProfile) -> Result<()> {
+    let lock_path = output.unwrap_or_else(|| project_root.join(openseal_core::LOCK_FILE_NAME));
+
+    println!("🔒 Computing identity lock for {:?}...", project_root);
+    let options = openseal_core::IdentityOptions {
+        canonical,
+        progress: terminal_progress(),
+        ..openseal_core::IdentityOptions::detect(project_root)
+    };
+    let lock = openseal_core::compute_lock_manifest_with_options(project_root, &options);
+    clear_progress(&options);
+    let lock = lock?;
+
+    if check {
+        let existing = openseal_core::LockManifest::load(&lock_path)?;
+        if existing != lock {
+            return Err(anyhow!(
+                "❌ {:?} is out of date.\n   Recorded Root: {}\n   Actual Root:   {}\n   Run 'openseal lock' and commit the result.",
+                lock_path, existing.root_hash.to_hex(), lock.root_hash.to_hex()
+            ));
+        }
+        println!("   ✅ Lock is up to date ({} files)", lock.files.len());
+        return Ok(());
+    }
+
+    lock.save(&lock_path)?;
+    println!("   📝 {:?} written ({} files)", lock_path, lock.files.len());
+    println!("   Root Hash: {}", lock.root_hash.to_hex());
+    Ok(())
+}
+
+/// Prints the identity of a project directory, optionally with the per-path explanation
+fn show_identity(project_root: &Path, explain: bool, canonical: openseal_core::CanonicalProfile, json: bool) -> Result<()> {
+    // Explaining reports unreadable entries as skipped instead of aborting on the first one
+    let options = openseal_core::IdentityOptions {
+        strict: !explain,
+        canonical,
+        progress: terminal_progress(),
+        ..openseal_core::IdentityOptions::detect(project_root)
+    };
+    let scan = openseal_core::compute_project_identity_explained(project_root, &options);
+    clear_progress(&options);
+    let (identity, explanation) = scan?;
+
+    if json {
+        let mut output = identity_json(&identity);
+        if explain {
+            output["entries"] = serde_json::to_value(&explanation.entries)?;
+        }
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("🧬 Identity of {:?}", project_root);
+    println!("   Root Hash: {}", identity.root_hash);
+    println!("   Files: {} ({} mutable)", identity.file_count, identity.mutable_files.len());
+    if let Some(preset) = identity.preset {
+        println!("   Preset: {}", preset);
+    }
+    if !identity.canonical.is_native() {
+        println!("   Canonical: {}", identity.canonical);
+    }
+    if explain {
+        println!();
+        for line in explanation.to_string().lines() {
+            println!("   {}", line);
+        }
+    }
+    Ok(())
+}
+
+/// Prints the identity of an archive, as the directory it extracts to would have
+fn show_archive_identity(archive_path: &Path, canonical: openseal_core::CanonicalProfile, json: bool) -> Result<()> {
+    let tree = openseal_core::ArchiveTree::read(
+        fs::File::open(archive_path).with_context(|| format!("Failed to open {:?}", archive_path))?,
+    )?;
+    let options = openseal_core::IdentityOptions { preset: Some(tree.detect_preset()), canonical, ..Default::default() };
+    let identity = tree.identity(&options)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&identity_json(&identity))?);
+        return Ok(());
+    }
+
+    println!("📦 Identity of archive {:?}", archive_path);
+    println!("   Root Hash: {}", identity.root_hash);
+    println!("   Files: {} ({} mutable)", identity.file_count, identity.mutable_files.len());
+    if let Some(preset) = identity.preset {
+        println!("   Preset: {}", preset);
+    }
+    if !identity.canonical.is_native() {
+        println!("   Canonical: {}", identity.canonical);
+    }
+    Ok(())
+}
+
+/// Prints the identity of a commit, as a clean checkout of it would have
+fn show_git_identity(repo_path: &Path, rev: &str, canonical: openseal_core::CanonicalProfile, json: bool) -> Result<()> {
+    let git_tree = openseal_core::GitTree::load(repo_path, rev)?;
+    let options = openseal_core::IdentityOptions { preset: Some(git_tree.tree().detect_preset()), canonical, ..Default::default() };
+    let identity = git_tree.identity(&options)?;
+    if json {
+        let mut value = identity_json(&identity);
+        value["commit"] = serde_json::json!(git_tree.commit);
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    println!("🌿 Identity of commit {} ({})", git_tree.commit, rev);
+    println!("   Root Hash: {}", identity.root_hash);
+    println!("   Files: {} ({} mutable)", identity.file_count, identity.mutable_files.len());
+    if let Some(preset) = identity.preset {
+        println!("   Preset: {}", preset);
+    }
+    if !identity.canonical.is_native() {
+        println!("   Canonical: {}", identity.canonical);
+    }
+    Ok(())
+}
+
+/// Verifies every blob of an image and prints its digests
+fn inspect_image(path: &Path, expected_root: Option<&str>, json: bool) -> Result<()> {
+    let images = openseal_core::inspect_image(path)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&images)?);
+    } else {
+        println!("🐳 Verified {} image(s) in {:?} (all blobs match their sha256)", images.len(), path);
+        for image in &images {
+            println!();
+            println!("   Reference: {}", image.reference.as_deref().unwrap_or("(none)"));
+            println!("   Manifest Digest: {}", image.manifest_digest.as_deref().unwrap_or("(not recorded by docker save)"));
+            println!("   Image ID (Config): {}", image.config_digest);
+            println!("   Layers: {}", image.layer_digests.len());
+        }
+    }
+
+    if let Some(expected) = expected_root {
+        if !images.iter().any(|image| image.matches(expected)) {
+            return Err(anyhow!("🚨 No image matches root hash {}", expected));
+        }
+        if !json {
+            println!();
+            println!("✅ Root hash {} confirmed offline", expected);
+        }
+    }
+    Ok(())
+}
+
+/// Builds an inclusion proof for a file of a flattened image and checks it against the filesystem root hash
+fn prove_image_file(
+    file: &str,
+    archive: &Path,
+    digest: Option<&str>,
+    expected_root: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let expected_root = match expected_root {
+        Some(root) => Some(root),
+        None => recorded_filesystem_root()?,
+    };
+
+    let filesystem = openseal_core::ImageFilesystem::load(archive, digest)?;
+    let identity = filesystem.identity()?;
+    let proof = filesystem.inclusion_proof(file)?;
+    println!("🗂️  {} is part of image {}", proof.path, filesystem.image.config_digest);
+    println!("   File Hash: {}", proof.file_hash.to_hex());
+    println!("   Filesystem Root Hash: {}", identity.root_hash);
+
+    if let Some(expected) = expected_root {
+        let expected_hash = expected.parse::<openseal_core::Digest>()?.as_blake3()
+            .ok_or_else(|| anyhow!("Filesystem root hashes are blake3 digests, got {}", expected))?;
+        if !openseal_core::verify_inclusion(&expected_hash, &proof.path, &proof.file_hash, &proof) {
+            return Err(anyhow!("🚨 Proof does not match filesystem root hash {}", expected));
+        }
+        println!("   ✅ Proof verified against {}", expected);
+    }
+
+    if let Some(output) = output {
+        fs::write(&output, serde_json::to_string_pretty(&proof)?)?;
+        println!("   📝 Proof written to {:?}", output);
+    }
+    Ok(())
+}
+
+/// Filesystem root hash recorded by `openseal build --archive`, if openseal.json has one
+fn recorded_filesystem_root() -> Result<Option<String>> {
+    let json_path = Path::new("openseal.json");
+    if !json_path.exists() {
+        return Ok(None);
+    }
+    let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(json_path)?)?;
+    Ok(manifest["identity"]["filesystem"]["root_hash"].as_str().map(str::to_string))
+}
+
+/// Live "files hashed" line on stderr for long scans, when stderr is a terminal
+fn terminal_progress() -> Option<openseal_core::ProgressCallback> {
+    if !std::io::stderr().is_terminal() {
+        return None;
+    }
+    let last_report = Mutex::new(Instant::now());
+    Some(openseal_core::ProgressCallback::new(move |progress| {
+        let Ok(mut last) = last_report.try_lock() else { return };
+        if last.elapsed() < Duration::from_millis(200) {
+            return;
+        }
+        *last = Instant::now();
+        if progress.discovery_complete {
+            eprint!("\r   ⏳ Hashed {}/{} files ({} MiB)\x1b[K",
+                progress.files_hashed, progress.files_discovered, progress.bytes_hashed / (1024 * 1024));
+        } else {
+            eprint!("\r   ⏳ Discovered {} files\x1b[K", progress.files_discovered);
+        }
+    }))
+}
+
+/// Erases the progress line once the scan is over
+fn clear_progress(options: &openseal_core::IdentityOptions) {
+    if options.progress.is_some() {
+        eprint!("\r\x1b[K");
+    }
+}
+
+/// JSON view of an identity with an algorithm-tagged root hash
+fn identity_json(identity: &openseal_core::ProjectIdentity) -> serde_json::Value {
+    serde_json::json!({
+        "root_hash": identity.root_hash,
+        "file_count": identity.file_count,
+        "mutable_files": identity.mutable_files,
+        "scheme": identity.scheme,
+        "preset": identity.preset,
+        "canonical": identity.canonical,
+    })
+}
+
+/// Loads a lock manifest from a file, or computes one live from a directory: with the options
+/// recorded in `other` when the other side is a lock file, so both sides are hashed alike
+fn load_or_compute_lock(path: &Path, other: Option<&openseal_core::LockManifest>) -> Result<openseal_core::LockManifest> {
+    match other {
+        _ if !path.is_dir() => openseal_core::LockManifest::load(path),
+        Some(other) => Ok(openseal_core::compute_lock_manifest_with_options(path, &other.options())?),
+        None => Ok(openseal_core::compute_lock_manifest(path)?),
+    }
+}
+
+/// Prints file-level differences between two identities
+fn diff_identities(old: &Path, new: &Path, json: bool) -> Result<()> {
+    // Lock files first, so a directory is scanned like the lock it is compared with
+    let (old_lock, new_lock) = if old.is_dir() {
+        let new_lock = load_or_compute_lock(new, None)?;
+        let other = (!new.is_dir()).then_some(&new_lock);
+        (load_or_compute_lock(old, other)?, new_lock)
+    } else {
+        let old_lock = load_or_compute_lock(old, None)?;
+        let new_lock = load_or_compute_lock(new, Some(&old_lock))?;
+        (old_lock, new_lock)
+    };
+    let diff = openseal_core::diff_manifests(&old_lock, &new_lock);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        println!("🔍 Comparing {:?} -> {:?}", old, new);
+        println!("   Old Root: {}", old_lock.root_hash.to_hex());
+        println!("   New Root: {}", new_lock.root_hash.to_hex());
+        println!();
+        for line in diff.to_string().lines() {
+            println!("   {}", line);
+        }
+    }
+
+    if !diff.is_empty() {
+        return Err(anyhow!("Identities differ ({} file(s) changed)", diff.change_count()));
+    }
+    Ok(())
+}
+
 /// Verifies a sealed response file
-fn verify_seal(response_path: &str, wax: &str, expected_root: Option<&str>) -> Result<()> {
+fn verify_seal(response_path: &str, wax: &str, expected_root: Option<&str>, options: &openseal_core::VerifyOptions) -> Result<()> {
     println!("🔍 Verifying seal...");
 
     // 1. Read and Parse JSON
@@ -337,22 +780,11 @@ fn verify_seal(response_path: &str, wax: &str, expected_root: Option<&str>) -> R
     // 2. Extract Components
     let openseal = json.get("openseal")
         .ok_or_else(|| anyhow!("Missing 'openseal' field"))?;
-    
-    let result = json.get("result")
-        .ok_or_else(|| anyhow!("Missing 'result' field"))?;
 
     let a_hash_hex = openseal.get("a_hash")
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow!("Missing or invalid 'a_hash'"))?;
         
-    let b_hash_hex = openseal.get("b_hash")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow!("Missing or invalid 'b_hash'"))?;
-        
-    let signature_hex = openseal.get("signature")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow!("Missing or invalid 'signature'"))?;
-        
     let pub_key_hex = openseal.get("pub_key")
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow!("Missing or invalid 'pub_key'"))?;
@@ -360,67 +792,66 @@ fn verify_seal(response_path: &str, wax: &str, expected_root: Option<&str>) -> R
     println!("   🔑 Public Key: {}", pub_key_hex);
     println!("   🆔 A-hash:    {}", a_hash_hex);
 
-    // 3. Compute Result Hash (Canonicalization)
-    // MUST match runtime logic: serde_json::to_string(&result)
-    let result_str = serde_json::to_string(result)?;
-    let result_hash = blake3::hash(result_str.as_bytes()).to_hex().to_string();
+    // sha256: image digest or blake3: project root (bare hex is a blake3 root)
+    let root_hash = expected_root.map(str::parse::<openseal_core::Digest>).transpose()?;
 
-    // 4. Reconstruct Message
-    // format!("{}{}{}{}", wax_hex, a_hash_hex, b_hash_hex, result_hash)
-    // IMPORTANT: Verify format matches openseal-runtime/src/lib.rs sign_payload
-    
-    // In runtime: wax_hex is passed as string
-    let message = format!("{}{}{}{}", wax, a_hash_hex, b_hash_hex, result_hash);
-    
-    // 5. Verify Signature
-    let pub_key_bytes = hex::decode(pub_key_hex)
-        .context("Failed to decode public key hex")?;
-    let pub_key: [u8; 32] = pub_key_bytes.try_into()
-        .map_err(|_| anyhow!("Invalid public key length"))?;
-    
-    let verifying_key = VerifyingKey::from_bytes(&pub_key)
-        .map_err(|_| anyhow!("Invalid public key format"))?;
+    // 3. Verify with Core: dispatches on the envelope's protocol version and algorithm suite,
+    // and refuses versions and suites it doesn't know
+    let report = openseal_core::verify_seal_with_options(&json, wax, root_hash.as_ref(), options)
+        .map_err(|e| anyhow!("❌ {}", e))?;
+    println!("   📜 Protocol:  {} ({})", report.protocol, report.suite);
 
-    let signature_vec = hex::decode(signature_hex)
-        .context("Failed to decode signature hex")?;
-    
-    let signature_bytes: [u8; 64] = signature_vec.try_into()
-        .map_err(|_| anyhow!("Invalid signature length. Expected 64 bytes."))?;
-        
-    let signature = Signature::from_bytes(&signature_bytes);
+    if !report.signature_verified {
+        return Err(anyhow!("❌ Signature Verification Failed: {}", report.message));
+    }
+    println!("   ✅ Signature Verified!");
+    if let Some(attested_at) = &report.attested_at {
+        println!("   🔁 Last re-attestation: {}", attested_at);
+    }
+    if let Some(issued_at) = &report.issued_at {
+        println!("   🕒 Issued at: {}", issued_at);
+    }
+    if let Some(expires_at) = &report.expires_at {
+        println!("   ⌛ Expires at: {}", expires_at);
+    }
+    if let Some(status) = report.status {
+        println!("   📨 Status: {}", status);
+    }
+    for (name, value) in report.headers.iter().flatten() {
+        println!("   📨 {}: {}", name, value.as_deref().unwrap_or("(absent)"));
+    }
+
+    // 4. Validity Period (expired, future-dated or older than --max-age)
+    use openseal_core::VerificationFailure::{Expired, FutureDated, TooOld, Undated};
+    if let Some(Expired | FutureDated | TooOld | Undated) = report.failure {
+        return Err(anyhow!("❌ {}", report.message));
+    }
 
-    match verifying_key.verify(message.as_bytes(), &signature) {
-        Ok(_) => {
-            println!("   ✅ Signature Verified!");
+    // 5. Optional: Verify Request Binding
+    if let Some(request) = &options.request {
+        println!("🔍 Verifying request...");
+        if !report.request_verified {
+            return Err(anyhow!("❌ {}", report.message));
         }
-        Err(e) => {
-            return Err(anyhow!("❌ Signature Verification Failed: {}", e));
+        println!("   ✅ Request Verified ({} {})", request.method, request.path);
+    }
+
+    // 6. Optional: Verify Response Status and Headers
+    if options.status.is_some() || !options.headers.is_empty() {
+        println!("🔍 Verifying response...");
+        if !report.response_verified {
+            return Err(anyhow!("❌ {}", report.message));
         }
+        println!("   ✅ Response Verified (Status and Headers Match the Seal)");
     }
 
-    // 6. Optional: Verify Identity (Root Hash)
-    if let Some(expected) = expected_root {
+    // 7. Optional: Verify Identity (Root Hash)
+    if let Some(root_hash) = &root_hash {
         println!("🔍 Verifying identity...");
-        
-        let root_hash_hex = if expected.starts_with("sha256:") {
-            &expected[7..]
-        } else {
-            expected
-        };
 
-        let root_hash_bytes = hex::decode(root_hash_hex)
-            .map_err(|e| anyhow!("Invalid digest hex: {}", e))?;
-            
-        let root_hash = blake3::Hash::from_bytes(
-            root_hash_bytes.as_slice().try_into()
-                .map_err(|_| anyhow!("Invalid hash length"))?
-        );
-
-        // a_hash = Blake3(RootHash || Wax) (Using Core)
-        let computed_a = openseal_core::compute_a_hash(&root_hash, wax);
-        let computed_a_hex = computed_a.to_hex().to_string();
-        
-        if computed_a_hex != a_hash_hex {
+        if !report.identity_verified {
+            // a_hash = Blake3(Algorithm || RootHash || Wax), untagged for V1 seals (Using Core)
+            let computed_a_hex = openseal_core::compute_a_hash(root_hash, wax, report.protocol).to_hex().to_string();
             return Err(anyhow!(
                 "❌ Identity Mismatch!\n   Expected A-hash: {}\n   Actual A-hash:   {}",
                 computed_a_hex, a_hash_hex
@@ -0,0 +1,73 @@
+//! Hashing throughput on a single large file: the buffered single-threaded path
+//! against `compute_file_hash` (parallel above `PARALLEL_HASH_THRESHOLD`), on a file with default
+//! permissions (read in chunks) and a read-only one (memory-mapped).
+//!
+//!     cargo bench -p openseal-core --bench large_file_hash
+//!
+//! `OPENSEAL_BENCH_MB` sets the file size (default 1024 MiB).
+
+use openseal_core::{compute_file_hash, PARALLEL_HASH_THRESHOLD};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 3;
+
+fn buffered_hash(path: &Path) -> std::io::Result<blake3::Hash> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = [0; 65536];
+    loop {
+        let count = file.read(&mut buffer)?;
+        if count == 0 { break; }
+        hasher.update(&buffer[..count]);
+    }
+    Ok(hasher.finalize())
+}
+
+/// Best of `RUNS` (the first run also warms the page cache).
+fn best_of(mut run: impl FnMut() -> blake3::Hash) -> (Duration, blake3::Hash) {
+    let mut best = Duration::MAX;
+    let mut hash = None;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        hash = Some(run());
+        best = best.min(start.elapsed());
+    }
+    (best, hash.unwrap())
+}
+
+fn main() -> anyhow::Result<()> {
+    let size_mb: u64 = std::env::var("OPENSEAL_BENCH_MB").ok().and_then(|v| v.parse().ok()).unwrap_or(1024);
+    let size = size_mb * 1024 * 1024;
+    assert!(size >= PARALLEL_HASH_THRESHOLD, "OPENSEAL_BENCH_MB must be at least the parallel threshold");
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("weights.bin");
+    let mut file = fs::File::create(&path)?;
+    let chunk: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+    for _ in 0..size_mb {
+        file.write_all(&chunk)?;
+    }
+    drop(file);
+
+    let (buffered, expected) = best_of(|| buffered_hash(&path).unwrap());
+    let (chunked, actual) = best_of(|| compute_file_hash(&path).unwrap());
+    assert_eq!(expected, actual, "parallel hashing must not change the content hash");
+
+    let mut read_only = fs::metadata(&path)?.permissions();
+    read_only.set_readonly(true);
+    fs::set_permissions(&path, read_only)?;
+    let (mapped, actual) = best_of(|| compute_file_hash(&path).unwrap());
+    assert_eq!(expected, actual, "parallel hashing must not change the content hash");
+
+    let throughput = |elapsed: Duration| size_mb as f64 / elapsed.as_secs_f64();
+    println!("{} MiB file, {} threads", size_mb, rayon::current_num_threads());
+    println!("  buffered, 1 thread:          {:>8.1?}  {:>8.0} MiB/s", buffered, throughput(buffered));
+    println!("  chunks + rayon (writable):   {:>8.1?}  {:>8.0} MiB/s  {:.2}x", chunked, throughput(chunked),
+        buffered.as_secs_f64() / chunked.as_secs_f64());
+    println!("  mmap + rayon (read-only):    {:>8.1?}  {:>8.0} MiB/s  {:.2}x", mapped, throughput(mapped),
+        buffered.as_secs_f64() / mapped.as_secs_f64());
+    Ok(())
+}
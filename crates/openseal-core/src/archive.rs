@@ -0,0 +1,621 @@
+use blake3::Hash;
+use flate2::read::GzDecoder;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use tar::EntryType;
+
+use crate::cache::Fingerprint;
+use crate::canonical::TextHasher;
+use crate::error::{IdentityError, IdentityResult};
+use crate::lock::{LockManifest, LOCK_FILE_NAME};
+use crate::merkle::{InclusionProof, LeafMeta};
+use crate::mutable::{MutablePatterns, MUTABLE_FILE_NAME};
+use crate::policy::{MutablePolicy, PolicyConfig, POLICY_FILE_NAME, SNIFF_LEN};
+use crate::preset::LanguagePreset;
+use crate::scan_filter::{is_pruned, IgnoreRules, IGNORE_FILES};
+use crate::symlink::{self, SymlinkPolicy};
+use crate::{identity_from_leaves, lock_from_leaves, proof_from_leaves, FileLeaf, IdentityOptions, ProjectIdentity};
+
+/// Rule files are kept in memory; anything larger is not a plausible rule file.
+const MAX_RULE_FILE_SIZE: u64 = 1 << 20;
+
+/// Maximum number of links followed when resolving a symlink (like the kernel's ELOOP limit).
+const MAX_LINK_HOPS: usize = 40;
+
+/// Image layer entries named `.wh.<name>` delete `<name>` from the layers below.
+const WHITEOUT_PREFIX: &str = ".wh.";
+
+/// Marks a directory whose content from the layers below is hidden entirely.
+const OPAQUE_WHITEOUT: &str = ".wh..wh..opq";
+
+#[derive(Debug, Clone)]
+enum ArchiveNode {
+    File(FileNode),
+    Symlink { target: PathBuf, mtime: u64 },
+}
+
+#[derive(Debug, Clone)]
+struct FileNode {
+    hash: Hash,
+    /// Hash with CRLF normalized to LF, for text files (see `CanonicalProfile::PortableText`).
+    text_hash: Option<Hash>,
+    meta: LeafMeta,
+    size: u64,
+    mtime: u64,
+    head: Vec<u8>,
+}
+
+/// The file tree of a tar archive, reduced to what an identity needs: content hashes,
+/// modes and link targets. Content is streamed through the hasher and never stored,
+/// except for rule files (`.opensealignore`, `.openseal_mutable`, ...).
+///
+/// The identity equals the one of the directory the archive extracts to. A `docker save`
+/// archive is a plain tar, so its identity covers the saved image files (manifest and layers).
+/// The flattened filesystem of an image is a tree too: see `ImageFilesystem`.
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveTree {
+    nodes: BTreeMap<String, ArchiveNode>,
+    rule_files: HashMap<String, String>,
+    /// A complete filesystem (an image): absolute and `..` link targets resolve inside it
+    rooted: bool,
+}
+
+impl ArchiveTree {
+    /// Reads a tar archive, gzip-compressed or not (detected from the magic bytes).
+    pub fn read<R: Read>(reader: R) -> IdentityResult<Self> {
+        Self::read_stripped(reader, 0)
+    }
+
+    /// Like `read`, dropping the first `strip_components` path components
+    /// (`tar --strip-components`), e.g. 1 for a release tarball wrapping `project-1.0/`.
+    pub fn read_stripped<R: Read>(reader: R, strip_components: usize) -> IdentityResult<Self> {
+        let mut tree = ArchiveTree::default();
+        tree.apply_layer(Layer::read(reader, strip_components, false)?)?;
+        Ok(tree)
+    }
+
+    /// An empty filesystem to apply image layers onto.
+    pub(crate) fn rooted() -> Self {
+        ArchiveTree { rooted: true, ..Default::default() }
+    }
+
+    /// Applies the entries of `layer` in order, as extraction (or an overlay mount) would:
+    /// later entries replace earlier ones, and whiteouts hide what the layers below provided.
+    pub(crate) fn apply_layer(&mut self, layer: Layer) -> IdentityResult<()> {
+        // Whiteouts only apply to lower layers, never to entries of the same layer
+        let mut added = HashSet::new();
+        for entry in layer.entries {
+            match entry {
+                LayerEntry::Whiteout(path) => {
+                    if !added.contains(&path) {
+                        self.remove(&path);
+                    }
+                    self.remove_below(&path, &added);
+                }
+                LayerEntry::Opaque(dir) => self.remove_below(&dir, &added),
+                LayerEntry::Directory(path) => self.make_room(&path, true),
+                LayerEntry::Node { path, node, rule_content } => {
+                    self.make_room(&path, false);
+                    if let Some(content) = rule_content {
+                        self.rule_files.insert(path.clone(), content);
+                    }
+                    self.nodes.insert(path.clone(), node);
+                    added.insert(path);
+                }
+                LayerEntry::HardLink { path, target } => {
+                    // Hard links carry no data: they share the content of an earlier entry
+                    let node = self.nodes.get(&target).cloned()
+                        .ok_or_else(|| invalid_entry(&path, "hard link to a missing entry"))?;
+                    let rule_content = self.rule_files.get(&target).cloned().filter(|_| is_rule_file(&path));
+                    self.make_room(&path, false);
+                    if let Some(content) = rule_content {
+                        self.rule_files.insert(path.clone(), content);
+                    }
+                    self.nodes.insert(path.clone(), node);
+                    added.insert(path);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Clears the way for a new entry at `path`: a file can't sit below a file, and an
+    /// entry replaces whatever was at its path before (including a whole directory).
+    fn make_room(&mut self, path: &str, is_dir: bool) {
+        let mut ancestor = Path::new(path).parent();
+        while let Some(dir) = ancestor.filter(|dir| !dir.as_os_str().is_empty()) {
+            self.remove(&dir.to_string_lossy());
+            ancestor = dir.parent();
+        }
+        self.remove(path);
+        if !is_dir {
+            self.remove_below(path, &HashSet::new());
+        }
+    }
+
+    /// Adds a regular file from another source than a tar stream (e.g. a git blob).
+    pub(crate) fn insert_file(&mut self, path: String, content: &[u8], mode: u32) -> IdentityResult<()> {
+        let (node, rule_content) = file_node(&path, &mut &content[..], content.len() as u64, mode, 0)?;
+        self.make_room(&path, false);
+        if let Some(content) = rule_content {
+            self.rule_files.insert(path.clone(), content);
+        }
+        self.nodes.insert(path, node);
+        Ok(())
+    }
+
+    pub(crate) fn insert_symlink(&mut self, path: String, target: PathBuf) {
+        self.make_room(&path, false);
+        self.nodes.insert(path, ArchiveNode::Symlink { target, mtime: 0 });
+    }
+
+    fn remove(&mut self, path: &str) {
+        self.nodes.remove(path);
+        self.rule_files.remove(path);
+    }
+
+    /// Removes everything below directory `dir` ("" is the root), except the `kept` paths.
+    fn remove_below(&mut self, dir: &str, kept: &HashSet<String>) {
+        let prefix = if dir.is_empty() { String::new() } else { format!("{}/", dir) };
+        let doomed: Vec<String> = self.nodes.range(prefix.clone()..)
+            .take_while(|(path, _)| path.starts_with(&prefix))
+            .map(|(path, _)| path.clone())
+            .filter(|path| !kept.contains(path))
+            .collect();
+        for path in doomed {
+            self.remove(&path);
+        }
+    }
+
+    /// Number of files and symlinks in the tree, before any exclusion.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.nodes.contains_key(path)
+    }
+
+    /// Detects the exclusion preset from marker files at the archive root.
+    pub fn detect_preset(&self) -> LanguagePreset {
+        LanguagePreset::detect_with(|marker| self.nodes.contains_key(marker))
+    }
+
+    pub fn identity(&self, options: &IdentityOptions) -> IdentityResult<ProjectIdentity> {
+        Ok(identity_from_leaves(options, &self.leaves(options)?))
+    }
+
+    pub fn lock_manifest(&self, options: &IdentityOptions) -> IdentityResult<LockManifest> {
+        Ok(lock_from_leaves(options, &self.leaves(options)?))
+    }
+
+    /// Proof that `path` is part of `identity(options)`, checked with `verify_inclusion`.
+    pub fn inclusion_proof(&self, options: &IdentityOptions, path: &str) -> IdentityResult<InclusionProof> {
+        proof_from_leaves(options, &self.leaves(options)?, path)
+    }
+
+    /// Same selection, ordering and hashing as the directory scan.
+    fn leaves(&self, options: &IdentityOptions) -> IdentityResult<Vec<FileLeaf>> {
+        let exclusion_rules = options.exclusion_rules();
+        let ignores = IgnoreRules::build(&self.rule_files)?;
+
+        let mut selected: Vec<(&str, &ArchiveNode)> = self.nodes.iter()
+            .filter(|(path, _)| path.as_str() != LOCK_FILE_NAME)
+            .filter(|(path, node)| {
+                let is_symlink = matches!(node, ArchiveNode::Symlink { .. });
+                !is_pruned(path, false, is_symlink && options.symlinks != SymlinkPolicy::Follow, &exclusion_rules, &ignores)
+            })
+            .map(|(path, node)| (path.as_str(), node))
+            .collect();
+        // The walker orders by path components, not by raw string ("a/b" < "a.txt")
+        selected.sort_by(|a, b| Path::new(a.0).cmp(Path::new(b.0)));
+
+        if self.nodes.contains_key(POLICY_FILE_NAME) && !selected.iter().any(|(path, _)| *path == POLICY_FILE_NAME) {
+            return Err(IdentityError::PolicyFileNotSealed);
+        }
+
+        let root = Path::new("");
+        let mutable_content = self.rule_files.get(MUTABLE_FILE_NAME).map(String::as_str).unwrap_or("");
+        let mut mutable_patterns = MutablePatterns::parse(root, mutable_content)?;
+        let policy_config = match self.rule_files.get(POLICY_FILE_NAME) {
+            Some(content) => PolicyConfig::parse(content)?,
+            None => PolicyConfig::default(),
+        };
+        let policy = MutablePolicy::from_config(root, &policy_config)?;
+
+        let mut leaves = Vec::with_capacity(selected.len());
+        for (raw_path, node) in selected {
+            let name = options.canonical.name(raw_path);
+            let path = name.as_str();
+            let leaf = match node {
+                ArchiveNode::Symlink { target, mtime } => {
+                    if options.symlinks == SymlinkPolicy::Reject {
+                        return Err(IdentityError::SymlinkRejected { path: path.into(), target: target.clone() });
+                    }
+                    if !self.rooted && symlink::escapes_root_lexically(Path::new(path), target) {
+                        return Err(IdentityError::SymlinkEscapes { path: path.into(), target: target.clone() });
+                    }
+                    if options.symlinks == SymlinkPolicy::Follow {
+                        let file = self.resolve_link(raw_path)?;
+                        file_leaf(path, file, *mtime, options, &mut mutable_patterns, &policy)?
+                    } else {
+                        FileLeaf {
+                            path: path.to_string(),
+                            file_hash: symlink::symlink_target_hash(target),
+                            meta: LeafMeta::SYMLINK,
+                            size: target.as_os_str().len() as u64,
+                            fingerprint: fingerprint(0, *mtime),
+                            mutable: false,
+                        }
+                    }
+                }
+                ArchiveNode::File(file) => file_leaf(path, file, file.mtime, options, &mut mutable_patterns, &policy)?,
+            };
+            leaves.push(leaf);
+        }
+        if !options.canonical.is_native() {
+            // Normalized names may sort differently than the archive's (NFD vs NFC)
+            leaves.sort_by(|a, b| Path::new(&a.path).cmp(Path::new(&b.path)));
+        }
+
+        for pattern in mutable_patterns.unmatched() {
+            eprintln!("Warning: .openseal_mutable pattern '{}' matched nothing", pattern);
+        }
+        Ok(leaves)
+    }
+
+    /// Resolves a symlink chain to the file it finally points to.
+    fn resolve_link(&self, path: &str) -> IdentityResult<&FileNode> {
+        let mut current = path.to_string();
+        for _ in 0..MAX_LINK_HOPS {
+            match self.nodes.get(&current) {
+                Some(ArchiveNode::File(file)) => return Ok(file),
+                Some(ArchiveNode::Symlink { target, .. }) => {
+                    let parent = Path::new(&current).parent().unwrap_or(Path::new(""));
+                    current = if self.rooted {
+                        resolve_in_root(parent, target)
+                    } else {
+                        symlink::normalize_lexically(&parent.join(target))
+                            .filter(|_| !target.has_root())
+                            .ok_or_else(|| invalid_entry(path, "symlink escapes the archive root"))?
+                            .to_string_lossy()
+                            .to_string()
+                    };
+                }
+                None => {
+                    let prefix = format!("{}/", current);
+                    let reason = if self.nodes.keys().any(|p| p.starts_with(&prefix)) {
+                        "following directory symlinks is not supported for archives"
+                    } else {
+                        "dangling symlink"
+                    };
+                    return Err(invalid_entry(path, reason));
+                }
+            }
+        }
+        Err(invalid_entry(path, "too many levels of symbolic links"))
+    }
+}
+
+fn file_leaf(
+    path: &str,
+    file: &FileNode,
+    mtime: u64,
+    options: &IdentityOptions,
+    mutable_patterns: &mut MutablePatterns,
+    policy: &MutablePolicy,
+) -> IdentityResult<FileLeaf> {
+    let mutable = mutable_patterns.is_mutable(path);
+    let file_hash = if mutable {
+        // SECURITY: Ensure we are not muting critical code files
+        policy.check_head(path, &file.head)?;
+        crate::compute_mutable_file_hash(Path::new(path))
+    } else if options.canonical.normalizes_line_endings() {
+        file.text_hash.unwrap_or(file.hash)
+    } else {
+        file.hash
+    };
+    Ok(FileLeaf {
+        path: path.to_string(),
+        file_hash,
+        meta: file.meta,
+        size: file.size,
+        fingerprint: fingerprint(file.size, mtime),
+        mutable,
+    })
+}
+
+/// Computes the identity of a tar / tar.gz / `docker save` archive file without extracting it.
+/// The exclusion preset is detected from marker files at the archive root.
+pub fn compute_archive_identity(archive_path: &Path) -> IdentityResult<ProjectIdentity> {
+    let file = fs::File::open(archive_path).map_err(IdentityError::io("open", archive_path))?;
+    let tree = ArchiveTree::read(file)?;
+    let options = IdentityOptions { preset: Some(tree.detect_preset()), ..Default::default() };
+    tree.identity(&options)
+}
+
+/// One tar entry, hashed but not yet applied to a tree.
+#[derive(Debug, Clone)]
+enum LayerEntry {
+    Node { path: String, node: ArchiveNode, rule_content: Option<String> },
+    HardLink { path: String, target: String },
+    Directory(String),
+    /// `.wh.<name>`: removes `<name>` as provided by the layers below
+    Whiteout(String),
+    /// `.wh..wh..opq`: hides everything the layers below put in the directory
+    Opaque(String),
+}
+
+/// The entries of one tar stream (an archive or an image layer), in archive order.
+/// Reading and applying are separate so that layers can be read in any order
+/// (as they come out of a `docker save` archive) and applied bottom-up afterwards.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Layer {
+    entries: Vec<LayerEntry>,
+}
+
+impl Layer {
+    /// Reads a tar stream, gzip-compressed or not. With `whiteouts`, the OCI / AUFS whiteout
+    /// files are interpreted as deletions instead of being kept as regular files.
+    pub(crate) fn read<R: Read>(reader: R, strip_components: usize, whiteouts: bool) -> IdentityResult<Self> {
+        let mut reader = BufReader::new(reader);
+        let magic = reader.fill_buf().map_err(archive_error)?;
+        if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            return Err(archive_error(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "zstd-compressed archives are not supported",
+            )));
+        }
+        if magic.starts_with(&[0x1f, 0x8b]) {
+            Self::read_tar(GzDecoder::new(reader), strip_components, whiteouts)
+        } else {
+            Self::read_tar(reader, strip_components, whiteouts)
+        }
+    }
+
+    fn read_tar<R: Read>(reader: R, strip_components: usize, whiteouts: bool) -> IdentityResult<Self> {
+        let mut entries = Vec::new();
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries().map_err(archive_error)? {
+            let mut entry = entry.map_err(archive_error)?;
+            let raw_path = entry.path().map_err(archive_error)?.into_owned();
+            let Some(path) = normalize_entry_path(&raw_path, strip_components)? else { continue };
+
+            if whiteouts {
+                let (dir, name) = match path.rsplit_once('/') {
+                    Some((dir, name)) => (dir, name),
+                    None => ("", path.as_str()),
+                };
+                if name == OPAQUE_WHITEOUT {
+                    entries.push(LayerEntry::Opaque(dir.to_string()));
+                    continue;
+                }
+                if let Some(hidden) = name.strip_prefix(WHITEOUT_PREFIX) {
+                    let hidden = if dir.is_empty() { hidden.to_string() } else { format!("{}/{}", dir, hidden) };
+                    entries.push(LayerEntry::Whiteout(hidden));
+                    continue;
+                }
+            }
+
+            let header = entry.header();
+            let mtime = header.mtime().unwrap_or(0);
+            let mode = header.mode().unwrap_or(0o644);
+            match header.entry_type() {
+                EntryType::Regular | EntryType::Continuous => {
+                    let size = entry.size();
+                    let (node, rule_content) = file_node(&path, &mut entry, size, mode, mtime)?;
+                    entries.push(LayerEntry::Node { path, node, rule_content });
+                }
+                EntryType::Link => {
+                    let link_name = entry.link_name().map_err(archive_error)?
+                        .ok_or_else(|| invalid_entry(&path, "hard link without a target"))?;
+                    let target = normalize_entry_path(&link_name, strip_components)?
+                        .ok_or_else(|| invalid_entry(&path, "hard link to the archive root"))?;
+                    entries.push(LayerEntry::HardLink { path, target });
+                }
+                EntryType::Symlink => {
+                    let target = entry.link_name().map_err(archive_error)?
+                        .ok_or_else(|| invalid_entry(&path, "symlink without a target"))?
+                        .into_owned();
+                    let node = ArchiveNode::Symlink { target, mtime };
+                    entries.push(LayerEntry::Node { path, node, rule_content: None });
+                }
+                EntryType::Directory => entries.push(LayerEntry::Directory(path)),
+                // Devices and FIFOs are never sealed
+                _ => {}
+            }
+        }
+        Ok(Layer { entries })
+    }
+}
+
+/// Resolves a link target the way a chroot would: absolute targets start at the root,
+/// and `..` never climbs above it.
+fn resolve_in_root(parent: &Path, target: &Path) -> String {
+    let mut parts: Vec<String> = if target.has_root() {
+        Vec::new()
+    } else {
+        parent.iter().map(|part| part.to_string_lossy().to_string()).collect()
+    };
+    for component in target.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            Component::ParentDir => { parts.pop(); }
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => {}
+        }
+    }
+    parts.join("/")
+}
+
+fn is_rule_file(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    IGNORE_FILES.contains(&name) || path == MUTABLE_FILE_NAME || path == POLICY_FILE_NAME
+}
+
+/// Hashes a regular file into a node, keeping the content of rule files.
+fn file_node<R: Read>(path: &str, reader: &mut R, size: u64, mode: u32, mtime: u64) -> IdentityResult<(ArchiveNode, Option<String>)> {
+    let keep_content = is_rule_file(path);
+    if keep_content && size > MAX_RULE_FILE_SIZE {
+        return Err(invalid_entry(path, "rule file is too large"));
+    }
+    let HashedEntry { hash, text_hash, size, head, content } = hash_entry(reader, keep_content)?;
+    let rule_content = content
+        .map(|content| String::from_utf8(content)
+            .map_err(|_| invalid_entry(path, "rule file is not valid UTF-8")))
+        .transpose()?;
+    let meta = LeafMeta::from_mode(mode);
+    Ok((ArchiveNode::File(FileNode { hash, text_hash, meta, size, mtime, head }), rule_content))
+}
+
+struct HashedEntry {
+    hash: Hash,
+    text_hash: Option<Hash>,
+    size: u64,
+    head: Vec<u8>,
+    content: Option<Vec<u8>>,
+}
+
+/// Streams an entry through BLAKE3, keeping the sniffing head and (for rule files) the content.
+/// Text content is also hashed with normalized line endings; binary content only once.
+fn hash_entry<R: Read>(entry: &mut R, keep_content: bool) -> IdentityResult<HashedEntry> {
+    let mut hasher = blake3::Hasher::new();
+    let mut text_hasher = Some(TextHasher::new());
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    let mut content = keep_content.then(Vec::new);
+    let mut size = 0u64;
+    let mut buffer = [0; 65536];
+    loop {
+        let count = entry.read(&mut buffer).map_err(archive_error)?;
+        if count == 0 { break; }
+        let chunk = &buffer[..count];
+        hasher.update(chunk);
+        if let Some(text) = text_hasher.as_mut() {
+            text.update(chunk);
+            if text.is_binary() {
+                text_hasher = None;
+            }
+        }
+        if head.len() < SNIFF_LEN {
+            let take = (SNIFF_LEN - head.len()).min(count);
+            head.extend_from_slice(&chunk[..take]);
+        }
+        if let Some(content) = content.as_mut() {
+            content.extend_from_slice(chunk);
+        }
+        size += count as u64;
+    }
+    let text_hash = text_hasher.map(TextHasher::finalize);
+    Ok(HashedEntry { hash: hasher.finalize(), text_hash, size, head, content })
+}
+
+/// Archive path as the extracted relative path (`/`-separated), or None if nothing is left after stripping.
+fn normalize_entry_path(raw: &Path, strip_components: usize) -> IdentityResult<Option<String>> {
+    let mut parts = Vec::new();
+    for component in raw.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            // Leading `/` and `./` are dropped on extraction
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => {}
+            Component::ParentDir => {
+                return Err(invalid_entry(&raw.to_string_lossy(), "path escapes the archive root"));
+            }
+        }
+    }
+    if parts.len() <= strip_components {
+        return Ok(None);
+    }
+    Ok(Some(parts[strip_components..].join("/")))
+}
+
+fn fingerprint(size: u64, mtime: u64) -> Fingerprint {
+    Fingerprint { size, mtime_secs: mtime as i64, mtime_nanos: 0, inode: 0 }
+}
+
+fn archive_error(source: std::io::Error) -> IdentityError {
+    IdentityError::Archive { source }
+}
+
+fn invalid_entry(path: &str, reason: &str) -> IdentityError {
+    IdentityError::InvalidArchiveEntry { path: path.to_string(), reason: reason.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute_project_identity;
+    use flate2::write::GzEncoder;
+    use tempfile::tempdir;
+
+    fn project(root: &Path) -> anyhow::Result<()> {
+        fs::create_dir_all(root.join("src/nested"))?;
+        fs::create_dir_all(root.join("logs"))?;
+        fs::create_dir_all(root.join("node_modules/dep"))?;
+        fs::write(root.join("package.json"), "{}")?;
+        fs::write(root.join("src/a.js"), "a")?;
+        fs::write(root.join("src/nested/b.js"), "b")?;
+        fs::write(root.join("src.txt"), "orders after src/ in the walker")?;
+        fs::write(root.join("logs/today.log"), "ignored")?;
+        fs::write(root.join("node_modules/dep/index.js"), "excluded")?;
+        fs::write(root.join("state.db"), "rows")?;
+        fs::write(root.join(".opensealignore"), "logs/\n")?;
+        fs::write(root.join(".openseal_mutable"), "*.db\n")?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::write(root.join("run.sh"), "#!/bin/sh")?;
+            fs::set_permissions(root.join("run.sh"), fs::Permissions::from_mode(0o755))?;
+            std::os::unix::fs::symlink("src/a.js", root.join("current.js"))?;
+        }
+        Ok(())
+    }
+
+    fn tar_of(root: &Path, prefix: &str) -> anyhow::Result<Vec<u8>> {
+        let mut builder = tar::Builder::new(Vec::new());
+        builder.follow_symlinks(false);
+        builder.append_dir_all(prefix, root)?;
+        Ok(builder.into_inner()?)
+    }
+
+    #[test]
+    fn test_archive_identity_matches_extracted_directory() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        project(dir.path())?;
+        let expected = compute_project_identity(dir.path())?;
+        assert_eq!(expected.preset, Some(LanguagePreset::Node));
+
+        let tar = tar_of(dir.path(), ".")?;
+        let tree = ArchiveTree::read(tar.as_slice())?;
+        let options = IdentityOptions { preset: Some(tree.detect_preset()), ..Default::default() };
+        assert_eq!(tree.identity(&options)?, expected);
+
+        // gzip is detected, and a wrapping directory can be stripped
+        let mut gz = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut gz, &tar_of(dir.path(), "project-1.0")?)?;
+        let tree = ArchiveTree::read_stripped(gz.finish()?.as_slice(), 1)?;
+        assert_eq!(tree.identity(&options)?, expected);
+        assert_eq!(tree.lock_manifest(&options)?, crate::compute_lock_manifest(dir.path())?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_rejects_escaping_paths() -> anyhow::Result<()> {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(1);
+        // Bypass the builder's own path validation
+        header.as_gnu_mut().unwrap().name[..9].copy_from_slice(b"../escape");
+        header.set_cksum();
+        builder.append(&header, &b"x"[..])?;
+        let tar = builder.into_inner()?;
+
+        let err = ArchiveTree::read(tar.as_slice()).unwrap_err();
+        assert!(matches!(err, IdentityError::InvalidArchiveEntry { .. }), "{}", err);
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use blake3::Hash;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::lock::hex_hash;
+
+const CACHE_VERSION: u32 = 1;
+
+/// Files modified this close to the scan are never cached: a later write within the
+/// same timestamp granularity would be indistinguishable ("racy git" problem).
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// Stat data that must be unchanged for a cached hash to be reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub size: u64,
+    pub mtime_secs: i64,
+    pub mtime_nanos: u32,
+    pub inode: u64,
+}
+
+impl Fingerprint {
+    pub fn from_metadata(metadata: &fs::Metadata) -> Self {
+        let (mtime_secs, mtime_nanos) = match metadata.modified().map(|t| t.duration_since(UNIX_EPOCH)) {
+            Ok(Ok(d)) => (d.as_secs() as i64, d.subsec_nanos()),
+            Ok(Err(before_epoch)) => (-(before_epoch.duration().as_secs() as i64), 0),
+            Err(_) => (0, 0),
+        };
+        #[cfg(unix)]
+        let inode = {
+            use std::os::unix::fs::MetadataExt;
+            metadata.ino()
+        };
+        #[cfg(not(unix))]
+        let inode = 0;
+
+        Fingerprint { size: metadata.len(), mtime_secs, mtime_nanos, inode }
+    }
+
+    fn modified_at(&self) -> Option<SystemTime> {
+        if self.mtime_secs < 0 {
+            return None;
+        }
+        Some(UNIX_EPOCH + Duration::new(self.mtime_secs as u64, self.mtime_nanos))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    fingerprint: Fingerprint,
+    #[serde(with = "hex_hash")]
+    hash: Hash,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    root: String,
+    entries: HashMap<String, CacheEntry>,
+}
+
+/// On-disk cache of content hashes, keyed by relative path and `Fingerprint`.
+///
+/// **Security Note**: the cache trusts stat data, which anyone who can write the files can forge.
+/// Use strict mode (`set_strict(true)`) for release sealing; results are bit-identical either way.
+/// The cache file itself is trusted too, so it lives in a private per-user directory.
+#[derive(Debug)]
+pub struct HashCache {
+    path: Option<PathBuf>,
+    root: String,
+    entries: HashMap<String, CacheEntry>,
+    strict: bool,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl HashCache {
+    /// A cache that lives only for the current process.
+    pub fn in_memory() -> Self {
+        HashCache {
+            path: None,
+            root: String::new(),
+            entries: HashMap::new(),
+            strict: false,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Loads the cache for `project_root` from `cache_path`.
+    /// A missing, corrupt or foreign cache file yields an empty cache.
+    pub fn load(cache_path: &Path, project_root: &Path) -> Self {
+        let root = fs::canonicalize(project_root)
+            .unwrap_or_else(|_| project_root.to_path_buf())
+            .to_string_lossy()
+            .to_string();
+        let entries = fs::read_to_string(cache_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<CacheFile>(&content).ok())
+            .filter(|file| file.version == CACHE_VERSION && file.root == root)
+            .map(|file| file.entries)
+            .unwrap_or_default();
+
+        HashCache { path: Some(cache_path.to_path_buf()), root, entries, ..HashCache::in_memory() }
+    }
+
+    /// Default cache location for a project: `$XDG_CACHE_HOME/openseal` (or the platform cache
+    /// directory), outside the project so it never affects the identity.
+    /// None when the user has no cache directory.
+    pub fn default_path(project_root: &Path) -> Option<PathBuf> {
+        let cache_dir = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(dirs::cache_dir)?;
+        let root = fs::canonicalize(project_root).unwrap_or_else(|_| project_root.to_path_buf());
+        let key = blake3::hash(root.to_string_lossy().as_bytes()).to_hex();
+        Some(cache_dir.join("openseal").join(format!("hashcache-{}.json", &key[..16])))
+    }
+
+    /// Strict mode ignores cached hashes (every file is re-hashed) and never writes the cache back.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Number of cached hashes reused / files hashed since this cache was loaded.
+    pub fn stats(&self) -> (usize, usize) {
+        (self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn lookup(&self, rel_path: &str, fingerprint: &Fingerprint) -> Option<Hash> {
+        let hit = if self.strict {
+            None
+        } else {
+            self.entries.get(rel_path)
+                .filter(|entry| entry.fingerprint == *fingerprint)
+                .map(|entry| entry.hash)
+        };
+        match hit {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        hit
+    }
+
+    /// Replaces the cache contents with the results of a scan that started at `scan_started`.
+    pub(crate) fn update<'a>(
+        &mut self,
+        scanned: impl Iterator<Item = (&'a str, Fingerprint, Hash)>,
+        scan_started: SystemTime,
+    ) {
+        let mut seen = HashSet::new();
+        for (rel_path, fingerprint, hash) in scanned {
+            seen.insert(rel_path.to_string());
+            let racy = fingerprint.modified_at()
+                .map(|mtime| mtime + RACY_WINDOW >= scan_started)
+                .unwrap_or(true);
+            if racy {
+                self.entries.remove(rel_path);
+            } else {
+                self.entries.insert(rel_path.to_string(), CacheEntry { fingerprint, hash });
+            }
+        }
+        self.entries.retain(|path, _| seen.contains(path));
+    }
+
+    /// Writes the cache back to disk (no-op for in-memory and strict caches).
+    /// The file is written next to `path` and renamed into place, so a symlink planted at `path`
+    /// is replaced rather than followed; a missing directory is created private (0700).
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        if self.strict {
+            return Ok(());
+        }
+        let file = CacheFile {
+            version: CACHE_VERSION,
+            root: self.root.clone(),
+            entries: self.entries.clone(),
+        };
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        create_private_dir(dir)
+            .with_context(|| format!("Failed to create hash cache directory: {:?}", dir))?;
+
+        let mut temp = tempfile::NamedTempFile::new_in(dir)
+            .with_context(|| format!("Failed to write hash cache: {:?}", path))?;
+        serde_json::to_writer(&mut temp, &file)?;
+        temp.persist(path)
+            .with_context(|| format!("Failed to write hash cache: {:?}", path))?;
+        Ok(())
+    }
+}
+
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    if dir.is_dir() {
+        return Ok(());
+    }
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(dir)
+}
@@ -0,0 +1,205 @@
+use blake3::Hash;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path};
+use std::str::FromStr;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::{IdentityError, IdentityResult};
+
+/// Content is text when its first bytes contain no NUL (git's heuristic).
+pub const TEXT_SNIFF_LEN: usize = 8000;
+
+/// How paths and content are normalized before hashing, so that checkouts of the same
+/// commit on Windows, macOS and Linux produce one identity. Recorded in the identity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CanonicalProfile {
+    /// Paths and bytes as found on disk (identities recorded without a profile).
+    #[default]
+    Native,
+    /// `/` separators and NFC file names (macOS stores NFD).
+    Portable,
+    /// `Portable`, plus CRLF -> LF in files detected as text.
+    PortableText,
+}
+
+impl CanonicalProfile {
+    pub fn is_native(&self) -> bool {
+        *self == CanonicalProfile::Native
+    }
+
+    pub fn normalizes_line_endings(&self) -> bool {
+        *self == CanonicalProfile::PortableText
+    }
+
+    /// The leaf path of a relative path: unchanged for `Native`, `/`-separated NFC otherwise.
+    pub fn path(&self, relative: &Path) -> String {
+        if self.is_native() {
+            return relative.to_string_lossy().to_string();
+        }
+        let joined = relative.components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => Some(part.to_string_lossy()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        self.name(&joined)
+    }
+
+    /// Unicode form of an already `/`-separated path (archive and git paths).
+    pub fn name(&self, path: &str) -> String {
+        if self.is_native() {
+            path.to_string()
+        } else {
+            path.nfc().collect()
+        }
+    }
+
+    /// Content hash of a file under this profile (line endings are only normalized by `PortableText`).
+    pub fn file_hash(&self, path: &Path) -> IdentityResult<Hash> {
+        if !self.normalizes_line_endings() {
+            return crate::compute_file_hash(path);
+        }
+        let mut file = fs::File::open(path).map_err(IdentityError::io("open", path))?;
+        let mut hasher = TextHasher::new();
+        let mut buffer = [0; 65536];
+        loop {
+            let count = file.read(&mut buffer).map_err(IdentityError::io("read", path))?;
+            if count == 0 { break; }
+            hasher.update(&buffer[..count]);
+        }
+        Ok(hasher.finalize())
+    }
+}
+
+impl fmt::Display for CanonicalProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            CanonicalProfile::Native => "native",
+            CanonicalProfile::Portable => "portable",
+            CanonicalProfile::PortableText => "portable-text",
+        })
+    }
+}
+
+impl FromStr for CanonicalProfile {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "native" => Ok(CanonicalProfile::Native),
+            "portable" => Ok(CanonicalProfile::Portable),
+            "portable-text" => Ok(CanonicalProfile::PortableText),
+            other => Err(format!("unknown canonical profile '{}' (native, portable, portable-text)", other)),
+        }
+    }
+}
+
+/// Streams content through BLAKE3, turning CRLF into LF once the content is known to be text.
+/// Binary content (a NUL within the first `TEXT_SNIFF_LEN` bytes) is hashed unchanged; lone CRs are kept.
+pub(crate) struct TextHasher {
+    hasher: blake3::Hasher,
+    head: Vec<u8>,
+    text: Option<bool>,
+    pending_cr: bool,
+}
+
+impl TextHasher {
+    pub(crate) fn new() -> Self {
+        TextHasher { hasher: blake3::Hasher::new(), head: Vec::new(), text: None, pending_cr: false }
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        if self.text.is_some() {
+            self.feed(data);
+            return;
+        }
+        self.head.extend_from_slice(data);
+        if self.head.len() >= TEXT_SNIFF_LEN {
+            self.decide();
+        }
+    }
+
+    /// Whether the content turned out to be binary (hashed unchanged).
+    pub(crate) fn is_binary(&self) -> bool {
+        self.text == Some(false)
+    }
+
+    pub(crate) fn finalize(mut self) -> Hash {
+        if self.text.is_none() {
+            self.decide();
+        }
+        if self.pending_cr {
+            self.hasher.update(b"\r");
+        }
+        self.hasher.finalize()
+    }
+
+    fn decide(&mut self) {
+        let head = std::mem::take(&mut self.head);
+        let sniffed = &head[..head.len().min(TEXT_SNIFF_LEN)];
+        self.text = Some(!sniffed.contains(&0));
+        self.feed(&head);
+    }
+
+    fn feed(&mut self, mut data: &[u8]) {
+        if self.text != Some(true) {
+            self.hasher.update(data);
+            return;
+        }
+        if self.pending_cr {
+            self.pending_cr = false;
+            if data.first() != Some(&b'\n') {
+                self.hasher.update(b"\r");
+            }
+        }
+        while let Some(pos) = data.iter().position(|&b| b == b'\r') {
+            self.hasher.update(&data[..pos]);
+            match data.get(pos + 1) {
+                // The CR of a CRLF is dropped; the LF goes out with the rest
+                Some(b'\n') => {}
+                Some(_) => { self.hasher.update(b"\r"); }
+                None => self.pending_cr = true,
+            }
+            data = &data[pos + 1..];
+        }
+        self.hasher.update(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_hash(chunks: &[&[u8]]) -> Hash {
+        let mut hasher = TextHasher::new();
+        for chunk in chunks {
+            hasher.update(chunk);
+        }
+        hasher.finalize()
+    }
+
+    #[test]
+    fn test_profiles_normalize_paths_and_line_endings() {
+        let decomposed = Path::new("src").join("cafe\u{301}.txt");
+        assert_eq!(CanonicalProfile::Portable.path(&decomposed), "src/caf\u{e9}.txt");
+        assert_eq!(CanonicalProfile::Native.path(&decomposed), decomposed.to_string_lossy());
+
+        // CRLF split across chunks, lone CRs kept, binary untouched
+        assert_eq!(text_hash(&[b"a\r", b"\nb\r\n"]), blake3::hash(b"a\nb\n"));
+        assert_eq!(text_hash(&[b"a\rb\r"]), blake3::hash(b"a\rb\r"));
+        assert_eq!(text_hash(&[b"\0\r\n"]), blake3::hash(b"\0\r\n"));
+
+        let head = b"x".repeat(TEXT_SNIFF_LEN);
+        assert_eq!(text_hash(&[&head, b"\r", b"\n"]), blake3::hash(&[&head[..], b"\n"].concat()));
+        let large: Vec<u8> = b"line\r\n".repeat(TEXT_SNIFF_LEN);
+        assert_eq!(text_hash(&[&large]), blake3::hash(&b"line\n".repeat(TEXT_SNIFF_LEN)));
+
+        assert_eq!("portable-text".parse::<CanonicalProfile>(), Ok(CanonicalProfile::PortableText));
+        assert_eq!(serde_json::to_value(CanonicalProfile::PortableText).unwrap(), "portable-text");
+    }
+}
@@ -0,0 +1,122 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::lock::{LockEntry, LockManifest};
+
+/// File-level differences between two identities.
+/// Paths are sorted; each path appears in exactly one list.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IdentityDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Content or mode changed.
+    pub modified: Vec<String>,
+    /// Declared mutable in one identity and immutable in the other.
+    pub mutability_changed: Vec<MutabilityChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MutabilityChange {
+    pub path: String,
+    pub now_mutable: bool,
+}
+
+impl IdentityDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.mutability_changed.is_empty()
+    }
+
+    pub fn change_count(&self) -> usize {
+        self.added.len() + self.removed.len() + self.modified.len() + self.mutability_changed.len()
+    }
+}
+
+/// Compares two lock manifests file by file (`old` is the expected/sealed side).
+/// Content hashes are scheme-independent, so manifests of different schemes can be compared.
+pub fn diff_manifests(old: &LockManifest, new: &LockManifest) -> IdentityDiff {
+    let old_files: BTreeMap<&str, &LockEntry> = old.files.iter().map(|e| (e.path.as_str(), e)).collect();
+    let new_files: BTreeMap<&str, &LockEntry> = new.files.iter().map(|e| (e.path.as_str(), e)).collect();
+
+    let mut diff = IdentityDiff::default();
+    for (path, before) in &old_files {
+        match new_files.get(path) {
+            None => diff.removed.push(path.to_string()),
+            Some(after) if before.mutable != after.mutable => {
+                diff.mutability_changed.push(MutabilityChange {
+                    path: path.to_string(),
+                    now_mutable: after.mutable,
+                });
+            }
+            Some(after) if before.hash != after.hash || before.mode != after.mode => {
+                diff.modified.push(path.to_string());
+            }
+            Some(_) => {}
+        }
+    }
+    for path in new_files.keys() {
+        if !old_files.contains_key(path) {
+            diff.added.push(path.to_string());
+        }
+    }
+    diff
+}
+
+impl fmt::Display for IdentityDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No file differences.");
+        }
+        for path in &self.added {
+            writeln!(f, "+ {} (added)", path)?;
+        }
+        for path in &self.removed {
+            writeln!(f, "- {} (removed)", path)?;
+        }
+        for path in &self.modified {
+            writeln!(f, "~ {} (modified)", path)?;
+        }
+        for change in &self.mutability_changed {
+            let (from, to) = if change.now_mutable { ("immutable", "mutable") } else { ("mutable", "immutable") };
+            writeln!(f, "! {} ({} -> {})", change.path, from, to)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute_lock_manifest;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_diff_reports_every_change_kind() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("keep.txt"), "same")?;
+        fs::write(dir.path().join("edit.txt"), "before")?;
+        fs::write(dir.path().join("gone.txt"), "bye")?;
+        fs::write(dir.path().join("data.db"), "rows")?;
+        let before = compute_lock_manifest(dir.path())?;
+
+        fs::write(dir.path().join("edit.txt"), "after")?;
+        fs::remove_file(dir.path().join("gone.txt"))?;
+        fs::write(dir.path().join("new.txt"), "hi")?;
+        fs::write(dir.path().join(".openseal_mutable"), "data.db\n")?;
+        let after = compute_lock_manifest(dir.path())?;
+
+        let diff = diff_manifests(&before, &after);
+        assert_eq!(diff.added, vec![".openseal_mutable", "new.txt"]);
+        assert_eq!(diff.removed, vec!["gone.txt"]);
+        assert_eq!(diff.modified, vec!["edit.txt"]);
+        assert_eq!(diff.mutability_changed, vec![MutabilityChange { path: "data.db".into(), now_mutable: true }]);
+        assert!(diff.to_string().contains("~ edit.txt (modified)"));
+
+        assert!(diff_manifests(&after, &after).is_empty());
+        Ok(())
+    }
+}
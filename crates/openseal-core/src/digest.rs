@@ -0,0 +1,161 @@
+use blake3::Hash;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Hash function a `Digest` was produced with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestAlgorithm {
+    /// Project identities (Merkle roots over BLAKE3 leaves).
+    Blake3,
+    /// Container image digests (`docker inspect`, registry `@sha256:`).
+    Sha256,
+}
+
+impl DigestAlgorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DigestAlgorithm::Blake3 => "blake3",
+            DigestAlgorithm::Sha256 => "sha256",
+        }
+    }
+}
+
+impl fmt::Display for DigestAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DigestError {
+    #[error("Unsupported digest algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+    #[error("Invalid {algorithm} digest {value:?}: expected 64 hex characters")]
+    InvalidHex { algorithm: DigestAlgorithm, value: String },
+}
+
+/// A root hash tagged with its algorithm, written `sha256:<hex>` or `blake3:<hex>`.
+/// Both algorithms produce 32 bytes; the tag keeps a SHA-256 image digest from being
+/// mistaken for (or verified as) a BLAKE3 project root with the same bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Digest {
+    algorithm: DigestAlgorithm,
+    bytes: [u8; 32],
+}
+
+impl Digest {
+    pub fn new(algorithm: DigestAlgorithm, bytes: [u8; 32]) -> Self {
+        Digest { algorithm, bytes }
+    }
+
+    pub fn blake3(hash: Hash) -> Self {
+        Digest::new(DigestAlgorithm::Blake3, *hash.as_bytes())
+    }
+
+    pub fn sha256(bytes: [u8; 32]) -> Self {
+        Digest::new(DigestAlgorithm::Sha256, bytes)
+    }
+
+    pub fn algorithm(&self) -> DigestAlgorithm {
+        self.algorithm
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.bytes
+    }
+
+    /// Hex of the bytes, without the algorithm prefix.
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.bytes)
+    }
+
+    /// The BLAKE3 hash, if this is a BLAKE3 digest (e.g. to check Merkle inclusion proofs).
+    pub fn as_blake3(&self) -> Option<Hash> {
+        (self.algorithm == DigestAlgorithm::Blake3).then(|| Hash::from_bytes(self.bytes))
+    }
+
+    /// Parses `<algorithm>:<hex>`. A bare hex string is a BLAKE3 root, as printed by earlier versions.
+    pub fn parse(value: &str) -> Result<Self, DigestError> {
+        let (algorithm, hex_part) = match value.split_once(':') {
+            Some(("blake3", hex_part)) => (DigestAlgorithm::Blake3, hex_part),
+            Some(("sha256", hex_part)) => (DigestAlgorithm::Sha256, hex_part),
+            Some((other, _)) => return Err(DigestError::UnsupportedAlgorithm(other.to_string())),
+            None => (DigestAlgorithm::Blake3, value),
+        };
+        let mut bytes = [0; 32];
+        hex::decode_to_slice(hex_part, &mut bytes)
+            .map_err(|_| DigestError::InvalidHex { algorithm, value: value.to_string() })?;
+        Ok(Digest::new(algorithm, bytes))
+    }
+}
+
+impl From<Hash> for Digest {
+    fn from(hash: Hash) -> Self {
+        Digest::blake3(hash)
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.algorithm, self.to_hex())
+    }
+}
+
+impl FromStr for Digest {
+    type Err = DigestError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Digest::parse(value)
+    }
+}
+
+impl Serialize for Digest {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Digest {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        /// Earlier identities serialized the BLAKE3 root as an array of 32 bytes.
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Text(String),
+            LegacyBytes([u8; 32]),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Text(text) => Digest::parse(&text).map_err(serde::de::Error::custom),
+            Repr::LegacyBytes(bytes) => Ok(Digest::new(DigestAlgorithm::Blake3, bytes)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_string_forms() {
+        let hash = blake3::hash(b"root");
+        let digest = Digest::blake3(hash);
+        assert_eq!(digest.to_string(), format!("blake3:{}", hash.to_hex()));
+        assert_eq!(digest.to_string().parse::<Digest>(), Ok(digest));
+        assert_eq!(hash.to_hex().parse::<Digest>(), Ok(digest));
+
+        let image: Digest = format!("sha256:{}", hash.to_hex()).parse().unwrap();
+        assert_eq!(image.algorithm(), DigestAlgorithm::Sha256);
+        assert_ne!(image, digest);
+        assert_eq!(image.as_blake3(), None);
+
+        assert!(matches!("md5:00".parse::<Digest>(), Err(DigestError::UnsupportedAlgorithm(_))));
+        assert!(matches!("sha256:abcd".parse::<Digest>(), Err(DigestError::InvalidHex { .. })));
+
+        // Legacy byte arrays and new strings both deserialize
+        let legacy: Digest = serde_json::from_value(serde_json::to_value(hash).unwrap()).unwrap();
+        assert_eq!(legacy, digest);
+        assert_eq!(serde_json::to_value(digest).unwrap(), serde_json::json!(digest.to_string()));
+    }
+}
@@ -0,0 +1,411 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Envelope version of a seal: which fields are signed and how the signed message is laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SealProtocol {
+    /// `wax || a_hash || b_hash || result_hash`, concatenated; nothing else is signed.
+    /// Seals without a `protocol` field.
+    #[default]
+    V1,
+    /// Domain-separated JSON of the signed fields, committing to the protocol and suite.
+    V2,
+}
+
+impl SealProtocol {
+    /// The version issued by this runtime.
+    pub const CURRENT: SealProtocol = SealProtocol::V2;
+
+    pub fn number(&self) -> u64 {
+        match self {
+            SealProtocol::V1 => 1,
+            SealProtocol::V2 => 2,
+        }
+    }
+
+    pub fn from_number(number: u64) -> Result<Self, SealError> {
+        match number {
+            1 => Ok(SealProtocol::V1),
+            2 => Ok(SealProtocol::V2),
+            other => Err(SealError::UnsupportedProtocol(other.to_string())),
+        }
+    }
+
+    /// Reads the `protocol` field of an envelope; seals without one are V1.
+    pub fn from_envelope(envelope: &serde_json::Value) -> Result<Self, SealError> {
+        match envelope.get("protocol") {
+            None => Ok(SealProtocol::V1),
+            Some(value) => value.as_u64()
+                .ok_or_else(|| SealError::UnsupportedProtocol(value.to_string()))
+                .and_then(SealProtocol::from_number),
+        }
+    }
+}
+
+impl fmt::Display for SealProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&format!("v{}", self.number()))
+    }
+}
+
+impl Serialize for SealProtocol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.number())
+    }
+}
+
+impl<'de> Deserialize<'de> for SealProtocol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        SealProtocol::from_number(u64::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Signature scheme and hash function of a seal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SealSuite {
+    /// Ed25519 signatures over BLAKE3 result hashes (the only suite of V1 seals).
+    #[default]
+    Ed25519Blake3,
+}
+
+impl SealSuite {
+    /// The suite issued by this runtime.
+    pub const CURRENT: SealSuite = SealSuite::Ed25519Blake3;
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SealSuite::Ed25519Blake3 => "ed25519-blake3",
+        }
+    }
+
+    /// Reads the `suite` field of an envelope. Only V1 seals may omit it.
+    pub fn from_envelope(envelope: &serde_json::Value, protocol: SealProtocol) -> Result<Self, SealError> {
+        match (envelope.get("suite"), protocol) {
+            (None, SealProtocol::V1) => Ok(SealSuite::Ed25519Blake3),
+            (None, _) => Err(SealError::MissingSuite(protocol)),
+            (Some(value), _) => value.as_str()
+                .ok_or_else(|| SealError::UnsupportedSuite(value.to_string()))?
+                .parse(),
+        }
+    }
+
+    /// Hex hash of the result bytes covered by the signature.
+    pub fn result_hash(&self, result: &[u8]) -> String {
+        match self {
+            SealSuite::Ed25519Blake3 => blake3::hash(result).to_hex().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SealSuite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl FromStr for SealSuite {
+    type Err = SealError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "ed25519-blake3" => Ok(SealSuite::Ed25519Blake3),
+            other => Err(SealError::UnsupportedSuite(other.to_string())),
+        }
+    }
+}
+
+impl Serialize for SealSuite {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for SealSuite {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SealError {
+    #[error("Unsupported seal protocol version {0} (this verifier supports 1 and 2)")]
+    UnsupportedProtocol(String),
+    #[error("Unsupported seal algorithm suite {0} (this verifier supports ed25519-blake3)")]
+    UnsupportedSuite(String),
+    #[error("Seal protocol {0} requires a 'suite' field")]
+    MissingSuite(SealProtocol),
+}
+
+/// Selected upstream response headers by lower-case name; `None` when the upstream didn't send one,
+/// so its absence is signed too. Repeated headers are joined with `, `.
+pub type SealedHeaders = BTreeMap<String, Option<String>>;
+
+/// The fields a seal signature covers, besides the protocol and suite themselves.
+#[derive(Debug, Clone, Serialize)]
+pub struct SealPayload<'a> {
+    pub wax: &'a str,
+    pub a_hash: &'a str,
+    pub b_hash: &'a str,
+    /// `SealSuite::result_hash` of the standardized result.
+    pub result_hash: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attested_at: Option<&'a str>,
+    /// Issuance and expiry times; not covered by V1 signatures.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issued_at: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<&'a str>,
+    /// `RequestDescription::digest` hex; not covered by V1 signatures.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_digest: Option<&'a str>,
+    /// Upstream status code and selected headers; not covered by V1 signatures.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<&'a SealedHeaders>,
+}
+
+#[derive(Serialize)]
+struct SignedFields<'a> {
+    protocol: SealProtocol,
+    suite: SealSuite,
+    #[serde(flatten)]
+    payload: &'a SealPayload<'a>,
+}
+
+impl SealPayload<'_> {
+    /// The exact bytes signed under `protocol` and `suite`.
+    pub fn message(&self, protocol: SealProtocol, suite: SealSuite) -> Vec<u8> {
+        match protocol {
+            // The layout of earlier versions: none of the optional fields are signed
+            SealProtocol::V1 => format!("{}{}{}{}", self.wax, self.a_hash, self.b_hash, self.result_hash).into_bytes(),
+            SealProtocol::V2 => {
+                let mut message = b"OPENSEAL_SEAL\n".to_vec();
+                let fields = SignedFields { protocol, suite, payload: self };
+                message.extend(serde_json::to_vec(&fields).expect("seal fields serialize"));
+                message
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compute_a_hash, verify_seal, verify_seal_with_options, Digest, RequestDescription, VerificationFailure, VerifyOptions,
+    };
+    use chrono::{DateTime, Duration, Utc};
+    use ed25519_dalek::{Signer, SigningKey};
+    use serde_json::json;
+
+    fn sealed_response(protocol: SealProtocol) -> (serde_json::Value, Digest) {
+        sealed_response_with(protocol, &[])
+    }
+
+    /// A response sealed with the optional signed `fields` (`issued_at`, `expires_at`, `request_digest`, `status`, `headers`).
+    fn sealed_response_with(protocol: SealProtocol, fields: &[(&str, serde_json::Value)]) -> (serde_json::Value, Digest) {
+        let field = |name: &str| fields.iter().find(|(field, _)| *field == name).map(|(_, value)| value);
+        let text = |name: &str| field(name).and_then(|value| value.as_str());
+        let headers = field("headers").map(|value| SealedHeaders::deserialize(value).unwrap());
+        let key = SigningKey::from_bytes(&[9; 32]);
+        let root = Digest::blake3(blake3::hash(b"root"));
+        let a_hash = compute_a_hash(&root, "wax", protocol).to_hex().to_string();
+        let b_hash = "b".repeat(64);
+        let result = serde_json::json!({ "price": 1 });
+        let result_hash = SealSuite::CURRENT.result_hash(serde_json::to_string(&result).unwrap().as_bytes());
+        let payload = SealPayload {
+            wax: "wax",
+            a_hash: &a_hash,
+            b_hash: &b_hash,
+            result_hash: &result_hash,
+            attested_at: Some("2026-01-22T10:00:00Z"),
+            issued_at: text("issued_at"),
+            expires_at: text("expires_at"),
+            request_digest: text("request_digest"),
+            status: field("status").and_then(|value| value.as_u64()).map(|code| code as u16),
+            headers: headers.as_ref(),
+        };
+        let mut envelope = serde_json::json!({
+            "signature": hex::encode(key.sign(&payload.message(protocol, SealSuite::CURRENT)).to_bytes()),
+            "pub_key": hex::encode(key.verifying_key().to_bytes()),
+            "a_hash": a_hash,
+            "b_hash": b_hash,
+            "attested_at": "2026-01-22T10:00:00Z",
+        });
+        for (name, value) in fields {
+            envelope[*name] = value.clone();
+        }
+        if protocol != SealProtocol::V1 {
+            envelope["protocol"] = serde_json::json!(protocol);
+            envelope["suite"] = serde_json::json!(SealSuite::CURRENT);
+        }
+        (serde_json::json!({ "result": result, "openseal": envelope }), root)
+    }
+
+    #[test]
+    fn test_verifier_dispatches_on_envelope_version() {
+        for protocol in [SealProtocol::V1, SealProtocol::V2] {
+            let (response, root) = sealed_response(protocol);
+            let report = verify_seal(&response, "wax", Some(&root)).unwrap();
+            assert!(report.valid, "{} seal should verify", protocol);
+            assert_eq!(report.protocol, protocol);
+            assert_eq!(report.suite, SealSuite::Ed25519Blake3);
+        }
+
+        // A V2 signature doesn't verify once relabelled as V1 (or the other way round)
+        let (mut response, root) = sealed_response(SealProtocol::V2);
+        response["openseal"].as_object_mut().unwrap().remove("protocol");
+        assert!(!verify_seal(&response, "wax", Some(&root)).unwrap().signature_verified);
+
+        let (mut response, root) = sealed_response(SealProtocol::V2);
+        response["openseal"]["protocol"] = serde_json::json!(3);
+        let err = verify_seal(&response, "wax", Some(&root)).unwrap_err();
+        assert_eq!(err.downcast_ref::<SealError>(), Some(&SealError::UnsupportedProtocol("3".into())));
+
+        response["openseal"]["protocol"] = serde_json::json!(2);
+        response["openseal"]["suite"] = serde_json::json!("ed448-sha3");
+        let err = verify_seal(&response, "wax", Some(&root)).unwrap_err();
+        assert_eq!(err.downcast_ref::<SealError>(), Some(&SealError::UnsupportedSuite("ed448-sha3".into())));
+
+        response["openseal"].as_object_mut().unwrap().remove("suite");
+        let err = verify_seal(&response, "wax", Some(&root)).unwrap_err();
+        assert_eq!(err.downcast_ref::<SealError>(), Some(&SealError::MissingSuite(SealProtocol::V2)));
+    }
+
+    #[test]
+    fn test_verifier_accepts_seals_of_earlier_versions() {
+        // Issued by the runtime before seals were versioned (untagged A-hash, no attestation time)
+        let mut response = json!({
+            "result": { "price": 1 },
+            "openseal": {
+                "signature": "a0778a161e4faf7d1b084118437dd282b83a8c5ed782cec6a52ce1b0799f6592b7e843d2fe48381652812390080bc0c5c1c0d0612719a7e8d9135631e09e6309",
+                "pub_key": "197f6b23e16c8532c6abc838facd5ea789be0c76b2920334039bfa8b3d368d61",
+                "a_hash": "a194ab6dfb57c5c993056ce534f7617812d4c361ed092d92f37ab3e9b299e3c5",
+                "b_hash": "10e5cf3d3c8a4f9f3468c8cc58eea84892a22fdadbc1acb22410190044c1d553",
+            }
+        });
+        let root = Digest::blake3(blake3::hash(b"root"));
+
+        let report = verify_seal(&response, "wax", Some(&root)).unwrap();
+        assert!(report.valid && report.identity_verified, "{}", report.message);
+        assert_eq!(report.protocol, SealProtocol::V1);
+        assert_eq!(report.failure, None);
+
+        // An attestation time next to a V1 signature isn't signed, so it isn't reported
+        response["openseal"]["attested_at"] = json!("2026-01-22T10:00:00Z");
+        let report = verify_seal(&response, "wax", Some(&root)).unwrap();
+        assert!(report.valid);
+        assert_eq!(report.attested_at, None);
+
+        let other = Digest::blake3(blake3::hash(b"other"));
+        assert_eq!(verify_seal(&response, "wax", Some(&other)).unwrap().failure, Some(VerificationFailure::IdentityMismatch));
+    }
+
+    #[test]
+    fn test_verifier_covers_attestation_time() {
+        let (mut response, root) = sealed_response(SealProtocol::V2);
+        let report = verify_seal(&response, "wax", Some(&root)).unwrap();
+        assert!(report.valid);
+        assert_eq!(report.attested_at.as_deref(), Some("2026-01-22T10:00:00Z"));
+
+        // A fresher attestation time can't be claimed after the fact
+        response["openseal"]["attested_at"] = json!("2026-01-23T10:00:00Z");
+        assert!(!verify_seal(&response, "wax", Some(&root)).unwrap().signature_verified);
+        response["openseal"].as_object_mut().unwrap().remove("attested_at");
+        assert!(!verify_seal(&response, "wax", Some(&root)).unwrap().signature_verified);
+    }
+
+    #[test]
+    fn test_verifier_checks_validity_period() {
+        let time = |value: &str| value.parse::<DateTime<Utc>>().unwrap();
+        let check = |response: &serde_json::Value, at: &str, max_age: Option<i64>| {
+            let options = VerifyOptions { at: time(at), max_age: max_age.map(Duration::seconds), ..Default::default() };
+            verify_seal_with_options(response, "wax", None, &options).unwrap()
+        };
+        let (response, _) = sealed_response_with(
+            SealProtocol::V2,
+            &[("issued_at", json!("2026-01-22T10:00:00Z")), ("expires_at", json!("2026-01-22T11:00:00Z"))],
+        );
+
+        let report = check(&response, "2026-01-22T10:30:00Z", Some(3600));
+        assert!(report.valid);
+        assert_eq!(report.issued_at.as_deref(), Some("2026-01-22T10:00:00Z"));
+        assert_eq!(check(&response, "2026-01-22T11:00:00Z", None).failure, Some(VerificationFailure::Expired));
+        assert_eq!(check(&response, "2026-01-22T09:00:00Z", None).failure, Some(VerificationFailure::FutureDated));
+        assert!(check(&response, "2026-01-22T09:59:30Z", None).valid, "within the clock skew");
+        assert_eq!(check(&response, "2026-01-22T10:30:00Z", Some(60)).failure, Some(VerificationFailure::TooOld));
+
+        // Times are signed: moving the expiry breaks the signature
+        let mut extended = response.clone();
+        extended["openseal"]["expires_at"] = serde_json::json!("2027-01-22T11:00:00Z");
+        assert_eq!(check(&extended, "2026-01-22T12:00:00Z", None).failure, Some(VerificationFailure::InvalidSignature));
+
+        // V1 seals carry no signed time: they pass without a maximum age, and are undated with one
+        let (legacy, _) = sealed_response_with(SealProtocol::V1, &[("issued_at", json!("2026-01-22T10:00:00Z"))]);
+        let report = check(&legacy, "2026-01-22T10:30:00Z", None);
+        assert!(report.valid);
+        assert_eq!(report.issued_at, None);
+        assert_eq!(check(&legacy, "2026-01-22T10:30:00Z", Some(3600)).failure, Some(VerificationFailure::Undated));
+    }
+
+    #[test]
+    fn test_verifier_checks_request_binding() {
+        let request = RequestDescription::new("POST", "/api/price?symbol=BTC", b"{}");
+        let digest = request.digest().to_hex().to_string();
+        let check = |response: &serde_json::Value, request: &RequestDescription| {
+            let options = VerifyOptions { request: Some(request.clone()), ..Default::default() };
+            verify_seal_with_options(response, "wax", None, &options).unwrap()
+        };
+        let (response, _) = sealed_response_with(SealProtocol::V2, &[("request_digest", json!(digest))]);
+
+        let report = check(&response, &RequestDescription::new("post", "/api//price?symbol=BTC", b"{}"));
+        assert!(report.valid && report.request_verified);
+        assert_eq!(report.request_digest.as_deref(), Some(digest.as_str()));
+
+        let report = check(&response, &RequestDescription::new("POST", "/api/price?symbol=ETH", b"{}"));
+        assert_eq!(report.failure, Some(VerificationFailure::RequestMismatch));
+        assert!(!report.request_verified);
+
+        // The digest is signed, and V1 seals are bound to no request
+        let mut redirected = response.clone();
+        redirected["openseal"]["request_digest"] = serde_json::json!(RequestDescription::new("GET", "/", b"").digest().to_hex().to_string());
+        assert_eq!(check(&redirected, &request).failure, Some(VerificationFailure::InvalidSignature));
+        let (legacy, _) = sealed_response_with(SealProtocol::V1, &[("request_digest", json!(digest))]);
+        assert_eq!(check(&legacy, &request).failure, Some(VerificationFailure::RequestMismatch));
+        assert!(verify_seal(&legacy, "wax", None).unwrap().valid);
+    }
+
+    #[test]
+    fn test_verifier_checks_response_status_and_headers() {
+        let sealed = json!({ "content-type": "application/json", "etag": null });
+        let (response, _) = sealed_response_with(SealProtocol::V2, &[("status", json!(404)), ("headers", sealed)]);
+        let check = |status: Option<u16>, headers: &[(&str, Option<&str>)]| {
+            let headers = headers.iter().map(|(name, value)| (name.to_string(), value.map(str::to_string))).collect();
+            let options = VerifyOptions { status, headers, ..Default::default() };
+            verify_seal_with_options(&response, "wax", None, &options).unwrap()
+        };
+
+        let report = check(Some(404), &[("Content-Type", Some("application/json")), ("ETag", None)]);
+        assert!(report.valid && report.response_verified);
+        assert_eq!(report.status, Some(404));
+        assert_eq!(report.headers.unwrap()["etag"], None);
+
+        for report in [
+            check(Some(200), &[]),
+            check(None, &[("content-type", Some("text/html"))]),
+            check(None, &[("etag", Some("\"v1\""))]),
+            check(None, &[("cache-control", None)]),
+        ] {
+            assert_eq!(report.failure, Some(VerificationFailure::ResponseMismatch), "{}", report.message);
+            assert!(!report.response_verified);
+        }
+
+        // An error page can't be relabelled as a success
+        let mut relabelled = response.clone();
+        relabelled["openseal"]["status"] = json!(200);
+        assert_eq!(verify_seal(&relabelled, "wax", None).unwrap().failure, Some(VerificationFailure::InvalidSignature));
+    }
+}
@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+
+use crate::policy::{PolicyViolation, POLICY_FILE_NAME};
+
+/// Why a project identity could not be computed.
+#[derive(Debug, thiserror::Error)]
+pub enum IdentityError {
+    /// The walker could not read an entry (strict mode only; otherwise the entry is skipped).
+    #[error("Failed to scan project: {source}")]
+    Walk {
+        #[source]
+        source: ignore::Error,
+    },
+    #[error("Failed to {action} {path:?}: {source}")]
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    Policy(#[from] PolicyViolation),
+    #[error("Symlink rejected by policy: {path:?} -> {target:?}")]
+    SymlinkRejected { path: PathBuf, target: PathBuf },
+    #[error("Symlink escapes the project root: {path:?} -> {target:?}")]
+    SymlinkEscapes { path: PathBuf, target: PathBuf },
+    #[error("{} must be part of the sealed identity (it is excluded or ignored)", POLICY_FILE_NAME)]
+    PolicyFileNotSealed,
+    #[error("Failed to read archive: {source}")]
+    Archive {
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Invalid archive entry {path:?}: {reason}")]
+    InvalidArchiveEntry { path: String, reason: String },
+    #[error("Git error: {source}")]
+    Git {
+        #[from]
+        source: git2::Error,
+    },
+    #[error("File is not part of the project identity: {0}")]
+    NotInIdentity(String),
+    /// The scan was aborted through the `CancellationToken` of its options.
+    #[error("Identity computation was cancelled")]
+    Cancelled,
+    /// A pattern of a rule file (`.openseal_mutable`, `.opensealignore`, `.openseal_policy.json` globs) is invalid.
+    #[error("Invalid pattern in {file:?}: {source}")]
+    InvalidPattern {
+        file: PathBuf,
+        #[source]
+        source: ignore::Error,
+    },
+    /// `.openseal_policy.json` is not a valid `PolicyConfig`.
+    #[error("Invalid {}: {source}", POLICY_FILE_NAME)]
+    InvalidPolicyFile {
+        #[source]
+        source: serde_json::Error,
+    },
+    /// A FIFO, socket or device: it has no content to seal (strict mode only; otherwise it is skipped).
+    #[error("Cannot seal {kind} {path:?}")]
+    SpecialFile { path: PathBuf, kind: &'static str },
+}
+
+pub type IdentityResult<T> = std::result::Result<T, IdentityError>;
+
+impl IdentityError {
+    pub(crate) fn io(action: &'static str, path: &Path) -> impl FnOnce(std::io::Error) -> Self {
+        let path = path.to_path_buf();
+        move |source| IdentityError::Io { action, path, source }
+    }
+
+    pub(crate) fn pattern(file: &Path) -> impl FnOnce(ignore::Error) -> Self {
+        let file = file.to_path_buf();
+        move |source| IdentityError::InvalidPattern { file, source }
+    }
+
+    /// The path the error is about, if known.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            IdentityError::Walk { source } => walk_error_path(source),
+            IdentityError::Io { path, .. }
+            | IdentityError::SymlinkRejected { path, .. }
+            | IdentityError::SymlinkEscapes { path, .. }
+            | IdentityError::SpecialFile { path, .. } => Some(path),
+            IdentityError::InvalidPattern { file, .. } => Some(file),
+            IdentityError::Policy(violation) => Some(Path::new(violation.path())),
+            IdentityError::InvalidArchiveEntry { path, .. } => Some(Path::new(path)),
+            _ => None,
+        }
+    }
+}
+
+/// Path an ignore walker error refers to, if any.
+pub(crate) fn walk_error_path(err: &ignore::Error) -> Option<&Path> {
+    match err {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::Loop { child, .. } => Some(child),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => walk_error_path(err),
+        ignore::Error::Partial(errs) => errs.iter().find_map(walk_error_path),
+        _ => None,
+    }
+}
@@ -0,0 +1,218 @@
+use ignore::gitignore::Gitignore;
+use ignore::{Match, WalkBuilder};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::error::IdentityResult;
+use crate::merkle::FileKind;
+use crate::mutable::MutablePatterns;
+use crate::{IdentityOptions, ProjectScan, SymlinkPolicy, LOCK_FILE_NAME};
+
+/// Ignore files honored by the identity walker, checked in this order per directory.
+const IGNORE_FILES: [&str; 2] = [".opensealignore", ".ignore"];
+
+/// Why a path is (or is not) part of an identity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryStatus {
+    /// Sealed by content (or by link target for symlinks).
+    Included,
+    /// Sealed by name only (`.openseal_mutable`).
+    Mutable,
+    /// Left out by `exclude_dirs` or a built-in rule.
+    Excluded,
+    /// Left out by `.opensealignore` (or `.ignore`).
+    Ignored,
+    /// Could not be read by the walker.
+    Skipped,
+}
+
+impl fmt::Display for EntryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EntryStatus::Included => "included",
+            EntryStatus::Mutable => "mutable",
+            EntryStatus::Excluded => "excluded",
+            EntryStatus::Ignored => "ignored",
+            EntryStatus::Skipped => "skipped",
+        };
+        // Pad here so `{:<8}` works on the status
+        f.pad(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExplainEntry {
+    /// Path relative to the project root. Excluded directories are listed once, not per file.
+    pub path: String,
+    pub status: EntryStatus,
+    /// The rule that decided the status.
+    pub reason: String,
+}
+
+/// Every path the identity walker saw, with the rule that decided its status. Sorted by path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IdentityExplanation {
+    pub entries: Vec<ExplainEntry>,
+}
+
+impl IdentityExplanation {
+    pub fn count(&self, status: EntryStatus) -> usize {
+        self.entries.iter().filter(|e| e.status == status).count()
+    }
+
+    pub fn get(&self, path: &str) -> Option<&ExplainEntry> {
+        self.entries.iter().find(|e| e.path == path)
+    }
+}
+
+impl fmt::Display for IdentityExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{:<8}  {}  ({})", entry.status, entry.path, entry.reason)?;
+        }
+        writeln!(
+            f,
+            "{} included, {} mutable, {} excluded, {} ignored, {} skipped",
+            self.count(EntryStatus::Included),
+            self.count(EntryStatus::Mutable),
+            self.count(EntryStatus::Excluded),
+            self.count(EntryStatus::Ignored),
+            self.count(EntryStatus::Skipped),
+        )
+    }
+}
+
+/// Builds the explanation for a finished scan.
+/// Ignored paths are found by re-walking the tree without ignore files.
+pub(crate) fn explain_scan(
+    root_path: &Path,
+    options: &IdentityOptions,
+    scan: &ProjectScan,
+) -> IdentityResult<IdentityExplanation> {
+    let patterns = MutablePatterns::load(root_path)?;
+    let mut entries = scan.omitted.clone();
+
+    for leaf in &scan.leaves {
+        let reason = match (leaf.meta.kind, leaf.mutable) {
+            (FileKind::Symlink, _) => "symlink (target path sealed)".to_string(),
+            (FileKind::File, true) => format!(
+                "sealed by name only: matches '{}' in .openseal_mutable",
+                patterns.deciding_pattern(&leaf.path).unwrap_or_default()
+            ),
+            (FileKind::File, false) => match patterns.deciding_pattern(&leaf.path) {
+                Some(rule) if rule.starts_with('!') => format!("sealed by content: re-included by '{}'", rule),
+                _ => "sealed by content".to_string(),
+            },
+        };
+        entries.push(ExplainEntry {
+            path: leaf.path.clone(),
+            status: if leaf.mutable { EntryStatus::Mutable } else { EntryStatus::Included },
+            reason,
+        });
+    }
+
+    let known: HashSet<String> = entries.iter().map(|e| e.path.clone()).collect();
+    let exclusion_rules = options.exclusion_rules();
+    let walker = WalkBuilder::new(root_path)
+        .standard_filters(false)
+        .follow_links(options.symlinks == SymlinkPolicy::Follow)
+        .filter_entry(move |entry| {
+            // Already reported by the identity walk
+            let excluded_dir = entry.file_type().is_some_and(|t| t.is_dir() || t.is_symlink())
+                && entry.file_name().to_str().is_some_and(|name| exclusion_rules.rule(name, entry.depth()).is_some());
+            let lock_file = entry.depth() == 1 && entry.file_name() == LOCK_FILE_NAME;
+            !excluded_dir && !lock_file
+        })
+        .build();
+
+    let mut matchers: HashMap<PathBuf, Vec<Gitignore>> = HashMap::new();
+    for entry in walker.flatten() {
+        let is_leaf = entry.file_type().is_some_and(|t| t.is_file() || t.is_symlink());
+        let rel = entry.path().strip_prefix(root_path).unwrap_or(entry.path());
+        let rel_str = options.canonical.path(rel);
+        if !is_leaf || known.contains(&rel_str) {
+            continue;
+        }
+        let reason = ignore_rule(root_path, rel, &mut matchers)
+            .unwrap_or_else(|| "ignored by an ignore file outside the project".to_string());
+        entries.push(ExplainEntry { path: rel_str, status: EntryStatus::Ignored, reason });
+    }
+
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(IdentityExplanation { entries })
+}
+
+/// Describes the ignore rule excluding `rel`: the deepest ignore file with a decisive match wins.
+fn ignore_rule(
+    root_path: &Path,
+    rel: &Path,
+    matchers: &mut HashMap<PathBuf, Vec<Gitignore>>,
+) -> Option<String> {
+    let abs = root_path.join(rel);
+    let mut dir = rel.parent();
+    while let Some(current) = dir {
+        let loaded = matchers.entry(current.to_path_buf()).or_insert_with(|| {
+            IGNORE_FILES.iter()
+                .map(|name| root_path.join(current).join(name))
+                .filter(|path| path.is_file())
+                .map(|path| Gitignore::new(path).0)
+                .collect()
+        });
+        for matcher in loaded.iter() {
+            match matcher.matched_path_or_any_parents(&abs, false) {
+                Match::Ignore(glob) => {
+                    let file = glob.from().map(|p| p.strip_prefix(root_path).unwrap_or(p));
+                    return Some(match file {
+                        Some(file) => format!("matches '{}' in {}", glob.original(), file.display()),
+                        None => format!("matches '{}'", glob.original()),
+                    });
+                }
+                Match::Whitelist(_) => return None,
+                Match::None => {}
+            }
+        }
+        dir = current.parent();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute_project_identity_explained;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_explain_reports_every_status_with_its_rule() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir_all(dir.path().join("src"))?;
+        fs::create_dir_all(dir.path().join("node_modules/pkg"))?;
+        fs::create_dir_all(dir.path().join("logs"))?;
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}")?;
+        fs::write(dir.path().join("node_modules/pkg/index.js"), "x")?;
+        fs::write(dir.path().join("logs/today.log"), "x")?;
+        fs::write(dir.path().join("state.db"), "rows")?;
+        fs::write(dir.path().join(".opensealignore"), "logs/\n")?;
+        fs::write(dir.path().join(".openseal_mutable"), "*.db\n")?;
+        fs::write(dir.path().join(LOCK_FILE_NAME), "{}")?;
+
+        let options = IdentityOptions::excluding(&["node_modules"]);
+        let (identity, explanation) = compute_project_identity_explained(dir.path(), &options)?;
+        assert_eq!(identity, crate::compute_project_identity_with_options(dir.path(), &options)?);
+
+        let status = |path: &str| explanation.get(path).map(|e| e.status);
+        assert_eq!(status("src/main.rs"), Some(EntryStatus::Included));
+        assert_eq!(status("state.db"), Some(EntryStatus::Mutable));
+        assert_eq!(status("node_modules"), Some(EntryStatus::Excluded));
+        assert_eq!(status(LOCK_FILE_NAME), Some(EntryStatus::Excluded));
+        assert_eq!(status("logs/today.log"), Some(EntryStatus::Ignored));
+        assert!(explanation.get("logs/today.log").unwrap().reason.contains("logs/"));
+        assert!(explanation.get("state.db").unwrap().reason.contains("*.db"));
+        assert_eq!(explanation.count(EntryStatus::Included) + explanation.count(EntryStatus::Mutable), identity.file_count);
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use git2::{ObjectType, Repository, TreeWalkMode, TreeWalkResult};
+use std::path::{Path, PathBuf};
+
+use crate::archive::ArchiveTree;
+use crate::error::IdentityResult;
+use crate::{IdentityOptions, ProjectIdentity};
+
+/// Git file mode of a symbolic link; the blob holds the link target.
+const GIT_SYMLINK_MODE: i32 = 0o120000;
+
+/// The tree of a commit, read from the object database of a local repository.
+/// Untracked and modified working-directory files can't leak in, so the identity is the one
+/// of a clean checkout of the commit (with no line-ending conversion or checkout filters).
+#[derive(Debug, Clone)]
+pub struct GitTree {
+    /// Full id of the resolved commit.
+    pub commit: String,
+    tree: ArchiveTree,
+}
+
+impl GitTree {
+    /// Resolves `rev` (commit id, branch, tag, `HEAD~1`, ...) in the repository containing `repo_path`.
+    /// Submodules are checked out as empty directories, so they contribute no files.
+    pub fn load(repo_path: &Path, rev: &str) -> IdentityResult<Self> {
+        let repo = Repository::discover(repo_path)?;
+        let commit = repo.revparse_single(rev)?.peel_to_commit()?;
+
+        let mut entries = Vec::new();
+        commit.tree()?.walk(TreeWalkMode::PreOrder, |dir, entry| {
+            if entry.kind() == Some(ObjectType::Blob) {
+                let name = String::from_utf8_lossy(entry.name_bytes());
+                entries.push((format!("{}{}", dir, name), entry.id(), entry.filemode()));
+            }
+            TreeWalkResult::Ok
+        })?;
+
+        let mut tree = ArchiveTree::default();
+        for (path, id, mode) in entries {
+            let blob = repo.find_blob(id)?;
+            if mode == GIT_SYMLINK_MODE {
+                tree.insert_symlink(path, link_target(blob.content()));
+            } else {
+                tree.insert_file(path, blob.content(), mode as u32)?;
+            }
+        }
+        Ok(GitTree { commit: commit.id().to_string(), tree })
+    }
+
+    pub fn tree(&self) -> &ArchiveTree {
+        &self.tree
+    }
+
+    pub fn identity(&self, options: &IdentityOptions) -> IdentityResult<ProjectIdentity> {
+        self.tree.identity(options)
+    }
+}
+
+#[cfg(unix)]
+fn link_target(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn link_target(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Computes the identity of a commit's tree, as `compute_project_identity` would on a clean checkout.
+/// The exclusion preset is detected from marker files in the tree.
+pub fn compute_git_identity(repo_path: &Path, rev: &str) -> IdentityResult<ProjectIdentity> {
+    let git_tree = GitTree::load(repo_path, rev)?;
+    let options = IdentityOptions { preset: Some(git_tree.tree().detect_preset()), ..Default::default() };
+    git_tree.identity(&options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute_project_identity;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn commit_all(repo: &Repository, message: &str) -> anyhow::Result<git2::Oid> {
+        let mut index = repo.index()?;
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+        index.write()?;
+        let tree = repo.find_tree(index.write_tree()?)?;
+        let signature = git2::Signature::now("test", "test@example.com")?;
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        Ok(repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)?)
+    }
+
+    #[test]
+    fn test_git_identity_matches_clean_checkout() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let repo = Repository::init(dir.path())?;
+        fs::create_dir_all(dir.path().join("src"))?;
+        fs::write(dir.path().join("package.json"), "{}")?;
+        fs::write(dir.path().join("src/index.js"), "v1")?;
+        fs::write(dir.path().join("state.db"), "rows")?;
+        fs::write(dir.path().join(".openseal_mutable"), "*.db\n")?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::write(dir.path().join("run.sh"), "#!/bin/sh")?;
+            fs::set_permissions(dir.path().join("run.sh"), fs::Permissions::from_mode(0o755))?;
+            std::os::unix::fs::symlink("src/index.js", dir.path().join("main.js"))?;
+        }
+        let first = commit_all(&repo, "first")?;
+        let checkout = compute_project_identity(dir.path())?;
+
+        // Untracked files and later commits don't change the identity of the commit
+        fs::write(dir.path().join("untracked.js"), "not committed")?;
+        assert_eq!(compute_git_identity(dir.path(), "HEAD")?, checkout);
+        fs::write(dir.path().join("src/index.js"), "v2")?;
+        commit_all(&repo, "second")?;
+        assert_eq!(compute_git_identity(dir.path(), &first.to_string())?, checkout);
+        assert_eq!(compute_git_identity(dir.path(), "HEAD~1")?, checkout);
+        assert_ne!(compute_git_identity(dir.path(), "HEAD")?, checkout);
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::archive::{ArchiveTree, Layer};
+use crate::error::IdentityResult;
+use crate::merkle::InclusionProof;
+use crate::oci::{self, ImageDigests, OciError, OciResult};
+use crate::{IdentityOptions, ProjectIdentity};
+
+/// The root filesystem of an image, as a container would see it: the layers applied
+/// bottom-up, whiteouts included. Its file-level identity lets auditors prove that a
+/// given file ships in the sealed image, which the opaque image digest cannot.
+#[derive(Debug, Clone)]
+pub struct ImageFilesystem {
+    pub image: ImageDigests,
+    tree: ArchiveTree,
+}
+
+impl ImageFilesystem {
+    /// Flattens the image identified by `digest` (registry digest or Image ID) from an
+    /// OCI layout or a `docker save` archive. Without a digest, the source must hold a single image.
+    /// All blobs are verified against their digests before any layer is applied.
+    pub fn load(path: &Path, digest: Option<&str>) -> OciResult<Self> {
+        let mut images = oci::inspect_image(path)?;
+        let image = match digest {
+            Some(digest) => {
+                let position = images.iter().position(|image| image.matches(digest))
+                    .ok_or_else(|| OciError::ImageNotFound(digest.to_string()))?;
+                images.swap_remove(position)
+            }
+            None if images.len() == 1 => images.remove(0),
+            None => return Err(OciError::AmbiguousImage(images.len())),
+        };
+
+        let wanted: HashSet<&str> = image.layer_paths.iter().map(String::as_str).collect();
+        let mut layers: HashMap<String, Layer> = HashMap::new();
+        oci::for_each_file(path, &wanted, |layer_path, reader| {
+            let layer = Layer::read(reader, 0, true)
+                .map_err(|source| OciError::Layer { path: layer_path.to_string(), source })?;
+            layers.insert(layer_path.to_string(), layer);
+            Ok(())
+        })?;
+
+        let mut tree = ArchiveTree::rooted();
+        for layer_path in &image.layer_paths {
+            let layer = layers.get(layer_path).cloned()
+                .ok_or_else(|| OciError::Missing(layer_path.clone()))?;
+            tree.apply_layer(layer)
+                .map_err(|source| OciError::Layer { path: layer_path.clone(), source })?;
+        }
+        Ok(ImageFilesystem { image, tree })
+    }
+
+    /// Options of the filesystem identity: every file, no exclusion preset.
+    pub fn options() -> IdentityOptions {
+        IdentityOptions::default()
+    }
+
+    pub fn tree(&self) -> &ArchiveTree {
+        &self.tree
+    }
+
+    pub fn identity(&self) -> IdentityResult<ProjectIdentity> {
+        self.tree.identity(&Self::options())
+    }
+
+    /// Proof that `path` (relative to the image root, e.g. `app/server.js`) is part of `identity()`.
+    pub fn inclusion_proof(&self, path: &str) -> IdentityResult<InclusionProof> {
+        self.tree.inclusion_proof(&Self::options(), path.trim_start_matches('/'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::verify_inclusion;
+    use crate::oci::Descriptor;
+    use flate2::write::GzEncoder;
+    use sha2::{Digest as _, Sha256};
+    use std::collections::BTreeMap;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn sha256(data: &[u8]) -> String {
+        format!("sha256:{}", hex::encode(Sha256::digest(data)))
+    }
+
+    fn layer(files: &[(&str, &[u8])], links: &[(&str, &str)]) -> anyhow::Result<Vec<u8>> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, path, *content)?;
+        }
+        for (path, target) in links {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_size(0);
+            builder.append_link(&mut header, path, target)?;
+        }
+        Ok(builder.into_inner()?)
+    }
+
+    fn write_blob(dir: &Path, data: &[u8]) -> anyhow::Result<Descriptor> {
+        let digest = sha256(data);
+        fs::create_dir_all(dir.join("blobs/sha256"))?;
+        fs::write(dir.join("blobs/sha256").join(&digest[7..]), data)?;
+        Ok(Descriptor { media_type: String::new(), digest, size: data.len() as u64, annotations: BTreeMap::new() })
+    }
+
+    #[test]
+    fn test_layers_flatten_with_whiteouts() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let image = dir.path().join("image");
+        let base = layer(
+            &[("app/server.js", b"v1"), ("app/debug.js", b"debug"), ("etc/conf/a", b"a"), ("etc/conf/b", b"b")],
+            &[("bin", "/usr/bin")],
+        )?;
+        let top = layer(
+            &[("app/server.js", b"v2"), ("app/.wh.debug.js", b""), ("etc/conf/.wh..wh..opq", b""), ("etc/conf/c", b"c")],
+            &[],
+        )?;
+        let mut gz = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut gz, &top)?;
+        let layers = vec![write_blob(&image, &base)?, write_blob(&image, &gz.finish()?)?];
+        let config = write_blob(&image, br#"{"rootfs":{"type":"layers","diff_ids":[]}}"#)?;
+        let manifest = write_blob(&image, serde_json::json!({ "config": config, "layers": layers }).to_string().as_bytes())?;
+        fs::write(image.join("index.json"), serde_json::json!({ "manifests": [manifest] }).to_string())?;
+
+        let filesystem = ImageFilesystem::load(&image, Some(&manifest.digest))?;
+        assert!(filesystem.tree().contains("bin"));
+        assert!(!filesystem.tree().contains("app/debug.js"));
+        assert!(!filesystem.tree().contains("etc/conf/a"));
+        let root = filesystem.identity()?.root_hash.as_blake3().unwrap();
+
+        // Same identity as the extracted root filesystem
+        let rootfs = dir.path().join("rootfs");
+        fs::create_dir_all(rootfs.join("app"))?;
+        fs::create_dir_all(rootfs.join("etc/conf"))?;
+        fs::write(rootfs.join("app/server.js"), "v2")?;
+        fs::write(rootfs.join("etc/conf/c"), "c")?;
+        let mut without_link = filesystem.tree().clone();
+        without_link.apply_layer(Layer::read(layer(&[(".wh.bin", b"")], &[])?.as_slice(), 0, true)?)?;
+        assert_eq!(without_link.identity(&ImageFilesystem::options())?, crate::compute_project_identity_with_options(&rootfs, &ImageFilesystem::options())?);
+
+        let proof = filesystem.inclusion_proof("/app/server.js")?;
+        assert!(verify_inclusion(&root, "app/server.js", &blake3::hash(b"v2"), &proof));
+        assert!(!verify_inclusion(&root, "app/server.js", &blake3::hash(b"v1"), &proof));
+        assert!(filesystem.inclusion_proof("app/debug.js").is_err());
+        Ok(())
+    }
+}
@@ -6,43 +6,221 @@ use ignore::WalkBuilder;
 use anyhow::{Result, Context};
 use std::io::Read;
 use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use progress::ScanMonitor;
+
+pub mod error;
+pub mod digest;
+pub mod merkle;
+pub mod archive;
+pub mod oci;
+pub mod image;
+pub mod git;
+pub mod lock;
+pub mod diff;
+pub mod explain;
+pub mod cache;
+pub mod mutable;
+pub mod policy;
+pub mod symlink;
+pub mod preset;
+pub mod canonical;
+pub mod progress;
+pub mod envelope;
+pub mod request;
+pub mod scan_filter;
+pub use error::{IdentityError, IdentityResult};
+pub use digest::{Digest, DigestAlgorithm, DigestError};
+pub use archive::{compute_archive_identity, ArchiveTree};
+pub use oci::{inspect_image, ImageDigests, OciError};
+pub use image::ImageFilesystem;
+pub use git::{compute_git_identity, GitTree};
+pub use merkle::{verify_inclusion, FileKind, IdentityScheme, InclusionProof, LeafMeta, MerkleTree};
+pub use lock::{LockEntry, LockManifest, LOCK_FILE_NAME};
+pub use diff::{diff_manifests, IdentityDiff, MutabilityChange};
+pub use explain::{EntryStatus, ExplainEntry, IdentityExplanation};
+pub use cache::{Fingerprint, HashCache};
+pub use mutable::{MutablePatterns, MUTABLE_FILE_NAME};
+pub use policy::{ExecutableKind, MutablePolicy, PolicyConfig, PolicyViolation, POLICY_FILE_NAME};
+pub use symlink::SymlinkPolicy;
+pub use preset::{ExclusionRules, LanguagePreset};
+pub use canonical::CanonicalProfile;
+pub use progress::{CancellationToken, ProgressCallback, ScanProgress};
+pub use envelope::{SealError, SealPayload, SealProtocol, SealSuite, SealedHeaders};
+pub use request::RequestDescription;
+pub use scan_filter::ScanFilter;
 
 // --- Phase 1: Merkle Tree Identity ---
 
 /// Represents the identity of a project, derived from its file structure and content.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ProjectIdentity {
-    pub root_hash: Hash, // A-hash component
+    /// Merkle root (`blake3:`), or the image digest (`sha256:`) for Docker-based seals. A-hash component.
+    pub root_hash: Digest,
     pub file_count: usize,
     pub mutable_files: Vec<String>,
+    /// Leaf encoding the root was computed with. Identities recorded without it are V1.
+    #[serde(default = "IdentityScheme::legacy")]
+    pub scheme: IdentityScheme,
+    /// Exclusion preset the root was computed with. Identities recorded without it used none.
+    #[serde(default)]
+    pub preset: Option<LanguagePreset>,
+    /// Path and line-ending normalization the root was computed with. Absent means `native`.
+    #[serde(default, skip_serializing_if = "CanonicalProfile::is_native")]
+    pub canonical: CanonicalProfile,
 }
 
 
-/// Scans a directory and computes its Merkle Root hash, excluding specified directories.
-/// This implements the Standard Exclusion Rules from AGNOSTICISM.md to extract "Pure Logic".
-pub fn compute_project_identity_excluding(
+/// Options controlling how a project identity is computed.
+/// `IdentityOptions::detect(root)` matches `compute_project_identity`; `default()` excludes nothing.
+#[derive(Debug, Clone)]
+pub struct IdentityOptions {
+    /// Directory names skipped at any depth (`/name`: only in the project root), in addition to the preset's.
+    pub exclude_dirs: Vec<String>,
+    /// Standard Exclusion Rules for the project type (recorded in the identity).
+    pub preset: Option<LanguagePreset>,
+    pub scheme: IdentityScheme,
+    pub symlinks: SymlinkPolicy,
+    /// Abort on entries the walker cannot read instead of skipping them (on by default).
+    /// Turn off only for diagnostics: a skipped file silently changes the root hash.
+    pub strict: bool,
+    /// Normalization of paths and text content (recorded in the identity).
+    pub canonical: CanonicalProfile,
+    /// Called as files are discovered and hashed.
+    pub progress: Option<ProgressCallback>,
+    /// Aborts the scan with `IdentityError::Cancelled` when triggered.
+    pub cancel: CancellationToken,
+}
+
+impl Default for IdentityOptions {
+    fn default() -> Self {
+        IdentityOptions {
+            exclude_dirs: Vec::new(),
+            preset: None,
+            scheme: IdentityScheme::CURRENT,
+            symlinks: SymlinkPolicy::default(),
+            strict: true,
+            canonical: CanonicalProfile::Native,
+            progress: None,
+            cancel: CancellationToken::default(),
+        }
+    }
+}
+
+impl IdentityOptions {
+    pub fn excluding(exclude_dirs: &[&str]) -> Self {
+        IdentityOptions {
+            exclude_dirs: exclude_dirs.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Options with the preset detected from the project's marker files.
+    pub fn detect(root_path: &Path) -> Self {
+        IdentityOptions {
+            preset: Some(LanguagePreset::detect(root_path)),
+            ..Default::default()
+        }
+    }
+
+    /// Every excluded directory with the rule that excludes it.
+    pub fn exclusion_rules(&self) -> ExclusionRules {
+        let mut rules = ExclusionRules::default();
+        if let Some(preset) = self.preset {
+            for name in preset.exclude_dirs() {
+                rules.insert(name, format!("excluded by {} preset: '{}'", preset, name));
+            }
+        }
+        for name in &self.exclude_dirs {
+            rules.insert(name, format!("excluded directory name '{}'", name));
+        }
+        rules
+    }
+}
+
+/// A single file's contribution to the project identity (one Merkle leaf).
+#[derive(Debug, Clone)]
+struct FileLeaf {
+    path: String,
+    file_hash: Hash,
+    meta: LeafMeta,
+    size: u64,
+    fingerprint: Fingerprint,
+    mutable: bool,
+}
+
+/// A walked entry that becomes a leaf.
+enum ScannedEntry {
+    File(PathBuf),
+    Symlink { path: PathBuf, target: PathBuf },
+}
+
+impl ScannedEntry {
+    fn path(&self) -> &Path {
+        match self {
+            ScannedEntry::File(path) | ScannedEntry::Symlink { path, .. } => path,
+        }
+    }
+}
+
+/// Result of walking a project: the sealed leaves plus every path the walker left out.
+struct ProjectScan {
+    leaves: Vec<FileLeaf>,
+    omitted: Vec<ExplainEntry>,
+}
+
+/// Walks the project and hashes every file, sorted by path.
+/// Content hashes are reused from `cache` when a file's fingerprint is unchanged.
+fn collect_file_leaves(
     root_path: &Path,
-    exclude_dirs: &[&str]
-) -> Result<ProjectIdentity> {
-    use std::collections::HashSet;
+    options: &IdentityOptions,
+    cache: Option<&HashCache>,
+) -> IdentityResult<Vec<FileLeaf>> {
+    Ok(scan_project(root_path, options, cache)?.leaves)
+}
+
+fn scan_project(
+    root_path: &Path,
+    options: &IdentityOptions,
+    cache: Option<&HashCache>,
+) -> IdentityResult<ProjectScan> {
+    use std::sync::{Arc, Mutex};
     
-    // Convert to owned Strings to satisfy 'static lifetime requirement of filter_entry closure
-    let exclude_set: HashSet<String> = exclude_dirs.iter().map(|s| s.to_string()).collect();
+    // Owned map to satisfy 'static lifetime requirement of filter_entry closure
+    let exclusion_rules = options.exclusion_rules();
+    let canonical_root = fs::canonicalize(root_path)
+        .map_err(IdentityError::io("resolve project root", root_path))?;
+    let omitted: Arc<Mutex<Vec<ExplainEntry>>> = Arc::default();
+    let filter_omitted = Arc::clone(&omitted);
+    let filter_root = root_path.to_path_buf();
     
     let walker = WalkBuilder::new(root_path)
         .hidden(false)
         .git_ignore(false)  // Do NOT use .gitignore - it's project-specific, not OpenSeal's concern
         .git_global(false)  // Ignore global git config
         .git_exclude(false) // Ignore git exclude
+        .follow_links(options.symlinks == SymlinkPolicy::Follow)
         .add_custom_ignore_filename(".opensealignore")  // OpenSeal's own ignore file
         .filter_entry(move |entry| {
-            // Skip directories that are in the exclude list
+            // The lock manifest records the root hash, so it can never be part of it
+            let omit = |reason: String| {
+                let path = entry.path().strip_prefix(&filter_root).unwrap_or(entry.path());
+                filter_omitted.lock().unwrap().push(ExplainEntry {
+                    path: path.to_string_lossy().to_string(),
+                    status: EntryStatus::Excluded,
+                    reason,
+                });
+                false
+            };
+            if entry.depth() == 1 && entry.file_name() == LOCK_FILE_NAME {
+                return omit("lock manifest (records the root hash)".to_string());
+            }
+            // Skip directories (or links standing in for them) that are in the exclude list
             if let Some(file_type) = entry.file_type() {
-                if file_type.is_dir() {
-                    if let Some(name) = entry.file_name().to_str() {
-                        if exclude_set.contains(name) {
-                            return false; // Skip this directory and all its contents
-                        }
+                if file_type.is_dir() || file_type.is_symlink() {
+                    if let Some(rule) = entry.file_name().to_str().and_then(|name| exclusion_rules.rule(name, entry.depth())) {
+                        // Skip this directory and all its contents
+                        return omit(rule.clone());
                     }
                 }
             }
@@ -51,111 +229,409 @@ pub fn compute_project_identity_excluding(
         .build();
 
     // Load mutable file patterns from .openseal_mutable if exists
-    let mutable_patterns = load_mutable_patterns(root_path);
+    let mut mutable_patterns = MutablePatterns::load(root_path)?;
+    let policy = MutablePolicy::load(root_path)?;
 
-    let mut file_paths: Vec<PathBuf> = Vec::new();
+    let monitor = ScanMonitor::new(options.progress.clone(), options.cancel.clone());
+    let mut entries: Vec<ScannedEntry> = Vec::new();
     for result in walker {
+        monitor.check()?;
         match result {
             Ok(entry) => {
-                if entry.file_type().map(|ft| ft.is_file()).unwrap_or(false) {
-                    file_paths.push(entry.path().to_owned());
+                let Some(file_type) = entry.file_type() else { continue };
+                if file_type.is_symlink() {
+                    // Only reachable when links are not followed
+                    let target = fs::read_link(entry.path())
+                        .map_err(IdentityError::io("read symlink", entry.path()))?;
+                    if options.symlinks == SymlinkPolicy::Reject {
+                        return Err(IdentityError::SymlinkRejected { path: entry.into_path(), target });
+                    }
+                    if symlink::escapes_root(&canonical_root, entry.path(), &target) {
+                        return Err(IdentityError::SymlinkEscapes { path: entry.into_path(), target });
+                    }
+                    entries.push(ScannedEntry::Symlink { path: entry.path().to_owned(), target });
+                    monitor.file_discovered();
+                } else if file_type.is_file() {
+                    // Followed links (or files below a followed directory link) must stay inside the root
+                    if entry.path_is_symlink() || options.symlinks == SymlinkPolicy::Follow {
+                        let resolved = fs::canonicalize(entry.path())
+                            .map_err(IdentityError::io("resolve path", entry.path()))?;
+                        if !resolved.starts_with(&canonical_root) {
+                            return Err(IdentityError::SymlinkEscapes { path: entry.into_path(), target: resolved });
+                        }
+                    }
+                    entries.push(ScannedEntry::File(entry.path().to_owned()));
+                    monitor.file_discovered();
+                } else if let Some(kind) = special_file_kind(&file_type) {
+                    if options.strict {
+                        return Err(IdentityError::SpecialFile { path: entry.into_path(), kind });
+                    }
+                    eprintln!("Warning: Skipping {}: {:?}", kind, entry.path());
+                    let path = entry.path().strip_prefix(root_path).unwrap_or(entry.path());
+                    omitted.lock().unwrap().push(ExplainEntry {
+                        path: path.to_string_lossy().to_string(),
+                        status: EntryStatus::Skipped,
+                        reason: format!("{} (no content to seal)", kind),
+                    });
                 }
             }
-            Err(err) => eprintln!("Warning: Skipping file due to error: {}", err),
+            Err(err) if options.strict => return Err(IdentityError::Walk { source: err }),
+            Err(err) => {
+                eprintln!("Warning: Skipping file due to error: {}", err);
+                let path = error::walk_error_path(&err)
+                    .map(|p| p.strip_prefix(root_path).unwrap_or(p).to_string_lossy().to_string())
+                    .unwrap_or_default();
+                omitted.lock().unwrap().push(ExplainEntry {
+                    path,
+                    status: EntryStatus::Skipped,
+                    reason: err.to_string(),
+                });
+            }
         }
     }
 
-    file_paths.sort();
-    
-    
+    monitor.discovery_complete();
+    entries.sort_by(|a, b| a.path().cmp(b.path()));
+
+    // The policy only means something if it is sealed along with the code it protects
+    let policy_path = root_path.join(POLICY_FILE_NAME);
+    if policy_path.exists() && entries.binary_search_by(|e| e.path().cmp(&policy_path)).is_err() {
+        return Err(IdentityError::PolicyFileNotSealed);
+    }
+
+    // Check which files are mutable (sequential: the matcher records which patterns were used)
+    let entries: Vec<(ScannedEntry, bool)> = entries.into_iter()
+        .map(|entry| {
+            let is_mutable = match &entry {
+                ScannedEntry::File(path) => {
+                    let relative_path = path.strip_prefix(root_path).unwrap_or(path);
+                    mutable_patterns.is_mutable(&options.canonical.path(relative_path))
+                }
+                ScannedEntry::Symlink { .. } => false,
+            };
+            (entry, is_mutable)
+        })
+        .collect();
+    for pattern in mutable_patterns.unmatched() {
+        eprintln!("Warning: .openseal_mutable pattern '{}' matched nothing", pattern);
+    }
 
-    let file_hashes: Vec<(Hash, Option<String>)> = file_paths.par_iter()
-        .map(|path| {
+    let mut leaves = entries.par_iter()
+        .map(|(entry, is_mutable)| {
+            monitor.check()?;
+            let path = entry.path();
             let relative_path = path.strip_prefix(root_path).unwrap_or(path);
-            let path_str = relative_path.to_string_lossy();
-            
-            // Check if mutable
-            let is_mutable = mutable_patterns.iter().any(|p| path_str == *p || path_str.ends_with(p));
+            let path_str = options.canonical.path(relative_path);
+            // Followed links are sealed as what they point to
+            let metadata = match entry {
+                ScannedEntry::File(_) => fs::metadata(path),
+                ScannedEntry::Symlink { .. } => fs::symlink_metadata(path),
+            }.map_err(IdentityError::io("read metadata of", path))?;
+            let meta = LeafMeta::from_metadata(&metadata);
+            let fingerprint = Fingerprint::from_metadata(&metadata);
 
-            if is_mutable {
+            if let ScannedEntry::Symlink { target, .. } = entry {
+                monitor.file_hashed(0);
+                return Ok(FileLeaf {
+                    path: path_str.to_string(),
+                    file_hash: symlink::symlink_target_hash(target),
+                    meta,
+                    size: metadata.len(),
+                    fingerprint,
+                    mutable: false,
+                });
+            }
+
+            if *is_mutable {
                 // SECURITY: Ensure we are not muting critical code files
-                validate_mutable_file_security(&path_str)?;
+                policy.check(&path_str, path)?;
 
-                // Track this mutable file
-                // Note: We can't directly modify mutable_files_found here due to parallel iteration
-                // So we'll collect and merge later
-                
                 // If mutable, we seal the FILENAME but explicitly ignore CONTENT
                 // Hash = Hash("MUTABLE_MARKER" || Filename)
                 // This ensures the *existence* of the file is frozen, but content can change.
-                Ok((compute_mutable_file_hash(relative_path), Some(path_str.to_string())))
+                monitor.file_hashed(0);
+                Ok(FileLeaf {
+                    path: path_str.to_string(),
+                    file_hash: compute_mutable_file_hash(Path::new(&path_str)),
+                    meta,
+                    size: metadata.len(),
+                    fingerprint,
+                    mutable: true,
+                })
             } else {
-                Ok((compute_file_hash(path)?, None))
+                // Cached hashes are of the raw content, so they don't apply to normalized text
+                let (file_hash, bytes_read) = if options.canonical.normalizes_line_endings() {
+                    (options.canonical.file_hash(path)?, metadata.len())
+                } else {
+                    match cache.and_then(|c| c.lookup(&path_str, &fingerprint)) {
+                        Some(hash) => (hash, 0),
+                        None => (compute_file_hash(path)?, metadata.len()),
+                    }
+                };
+                monitor.file_hashed(bytes_read);
+                Ok(FileLeaf {
+                    path: path_str.to_string(),
+                    file_hash,
+                    meta,
+                    size: metadata.len(),
+                    fingerprint,
+                    mutable: false,
+                })
             }
         })
-        .collect::<Result<Vec<(Hash, Option<String>)>>>()?;
+        .collect::<IdentityResult<Vec<FileLeaf>>>()?;
+    if !options.canonical.is_native() {
+        // Normalized names may sort differently than on disk (NFD vs NFC)
+        leaves.sort_by(|a, b| Path::new(&a.path).cmp(Path::new(&b.path)));
+    }
 
-    // Extract mutable files and hashes
-    let (hashes, mut_files): (Vec<Hash>, Vec<Option<String>>) = file_hashes.into_iter().unzip();
-    let mutable_files_found: Vec<String> = mut_files.into_iter().filter_map(|x| x).collect();
+    let omitted = std::mem::take(&mut *omitted.lock().unwrap());
+    Ok(ProjectScan { leaves, omitted })
+}
 
-    let root_hash = compute_merkle_root(&hashes);
+/// Scans a directory and computes its Merkle Root hash, excluding specified directories.
+/// This implements the Standard Exclusion Rules from AGNOSTICISM.md to extract "Pure Logic".
+pub fn compute_project_identity_excluding(
+    root_path: &Path,
+    exclude_dirs: &[&str]
+) -> IdentityResult<ProjectIdentity> {
+    compute_project_identity_with_options(root_path, &IdentityOptions::excluding(exclude_dirs))
+}
 
-    Ok(ProjectIdentity {
-        root_hash,
-        file_count: file_paths.len(),
-        mutable_files: mutable_files_found,
+/// Computes the identity under a specific leaf scheme.
+/// Use the scheme recorded alongside an expected root to re-verify identities sealed by older versions.
+pub fn compute_project_identity_with_scheme(
+    root_path: &Path,
+    exclude_dirs: &[&str],
+    scheme: IdentityScheme,
+) -> IdentityResult<ProjectIdentity> {
+    let options = IdentityOptions { scheme, ..IdentityOptions::excluding(exclude_dirs) };
+    compute_project_identity_with_options(root_path, &options)
+}
+
+/// Describes a FIFO, socket or device; None for files, directories and links.
+fn special_file_kind(file_type: &fs::FileType) -> Option<&'static str> {
+    if file_type.is_file() || file_type.is_dir() || file_type.is_symlink() {
+        return None;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if file_type.is_fifo() {
+            return Some("FIFO");
+        }
+        if file_type.is_socket() {
+            return Some("socket");
+        }
+        if file_type.is_block_device() || file_type.is_char_device() {
+            return Some("device");
+        }
+    }
+    Some("special file")
+}
+
+/// Computes the identity with full control over exclusions, scheme and symlink handling.
+pub fn compute_project_identity_with_options(
+    root_path: &Path,
+    options: &IdentityOptions,
+) -> IdentityResult<ProjectIdentity> {
+    let leaves = collect_file_leaves(root_path, options, None)?;
+    Ok(identity_from_leaves(options, &leaves))
+}
+
+/// Computes the identity, reusing content hashes of unchanged files from `cache`.
+/// The result is bit-identical to `compute_project_identity_with_options`; the cache is
+/// refreshed in memory and must be persisted with `HashCache::save`.
+pub fn compute_project_identity_cached(
+    root_path: &Path,
+    options: &IdentityOptions,
+    cache: &mut HashCache,
+) -> IdentityResult<ProjectIdentity> {
+    let scan_started = std::time::SystemTime::now();
+    let leaves = collect_file_leaves(root_path, options, Some(cache))?;
+    if options.canonical.normalizes_line_endings() {
+        return Ok(identity_from_leaves(options, &leaves));
+    }
+    cache.update(
+        leaves.iter()
+            .filter(|l| !l.mutable && l.meta.kind == FileKind::File)
+            .map(|l| (l.path.as_str(), l.fingerprint, l.file_hash)),
+        scan_started,
+    );
+    Ok(identity_from_leaves(options, &leaves))
+}
+
+fn identity_from_leaves(options: &IdentityOptions, leaves: &[FileLeaf]) -> ProjectIdentity {
+    ProjectIdentity {
+        root_hash: Digest::blake3(build_merkle_tree(options.scheme, leaves).root()),
+        file_count: leaves.len(),
+        mutable_files: leaves.iter().filter(|l| l.mutable).map(|l| l.path.clone()).collect(),
+        scheme: options.scheme,
+        preset: options.preset,
+        canonical: options.canonical,
+    }
+}
+
+/// Computes the identity together with a report of every path that was sealed, left out, or skipped.
+pub fn compute_project_identity_explained(
+    root_path: &Path,
+    options: &IdentityOptions,
+) -> IdentityResult<(ProjectIdentity, IdentityExplanation)> {
+    let scan = scan_project(root_path, options, None)?;
+    let identity = identity_from_leaves(options, &scan.leaves);
+    let explanation = explain::explain_scan(root_path, options, &scan)?;
+    Ok((identity, explanation))
+}
+
+/// Builds an inclusion proof showing that `relative_path` is part of the project identity.
+/// Verifiers check it with `verify_inclusion` against the published root hash.
+pub fn compute_inclusion_proof_excluding(
+    root_path: &Path,
+    exclude_dirs: &[&str],
+    relative_path: &str,
+) -> IdentityResult<InclusionProof> {
+    compute_inclusion_proof_with_options(root_path, &IdentityOptions::excluding(exclude_dirs), relative_path)
+}
+
+pub fn compute_inclusion_proof_with_options(
+    root_path: &Path,
+    options: &IdentityOptions,
+    relative_path: &str,
+) -> IdentityResult<InclusionProof> {
+    let leaves = collect_file_leaves(root_path, options, None)?;
+    proof_from_leaves(options, &leaves, relative_path)
+}
+
+fn proof_from_leaves(options: &IdentityOptions, leaves: &[FileLeaf], relative_path: &str) -> IdentityResult<InclusionProof> {
+    let leaf_index = leaves.iter()
+        .position(|l| l.path == relative_path)
+        .ok_or_else(|| IdentityError::NotInIdentity(relative_path.to_string()))?;
+    let tree = build_merkle_tree(options.scheme, leaves);
+
+    Ok(InclusionProof {
+        scheme: options.scheme,
+        path: leaves[leaf_index].path.clone(),
+        file_hash: leaves[leaf_index].file_hash,
+        meta: leaves[leaf_index].meta,
+        leaf_index,
+        leaf_count: leaves.len(),
+        steps: tree.proof_steps(leaf_index).expect("leaf index is in range"),
     })
 }
 
-/// Scans a directory and computes its Merkle Root hash (backwards compatible wrapper).
-pub fn compute_project_identity(root_path: &Path) -> Result<ProjectIdentity> {
-    compute_project_identity_excluding(root_path, &[])
+/// Builds an inclusion proof using the default exclusion rules of `compute_project_identity`.
+pub fn compute_inclusion_proof(root_path: &Path, relative_path: &str) -> IdentityResult<InclusionProof> {
+    compute_inclusion_proof_with_options(root_path, &IdentityOptions::detect(root_path), relative_path)
 }
 
-/// SECURITY: Enforce blacklist on mutable files to prevent Backdoor Injection.
-fn validate_mutable_file_security(path_str: &str) -> Result<()> {
-    let lower = path_str.to_lowercase();
-    let dangerous_extensions = [
-        ".rs", ".js", ".ts", ".py", ".go", ".c", ".cpp", ".h", ".hpp", 
-        ".wasm", ".sh", ".bat", ".cmd", ".json", ".toml", ".yaml", ".yml"
-    ];
+/// Computes the lock manifest (`openseal.lock`) listing every file of the identity.
+pub fn compute_lock_manifest_with_options(root_path: &Path, options: &IdentityOptions) -> IdentityResult<LockManifest> {
+    let leaves = collect_file_leaves(root_path, options, None)?;
+    Ok(lock_from_leaves(options, &leaves))
+}
 
-    for ext in dangerous_extensions {
-        if lower.ends_with(ext) {
-            anyhow::bail!(
-                "SECURITY VIOLATION: '{}' cannot be mutable. Code/Config files must be immutable.", 
-                path_str
-            );
-        }
+fn lock_from_leaves(options: &IdentityOptions, leaves: &[FileLeaf]) -> LockManifest {
+    let scheme = options.scheme;
+    let files: Vec<LockEntry> = leaves.iter()
+        .map(|l| LockEntry {
+            path: l.path.clone(),
+            hash: l.file_hash,
+            leaf: merkle::leaf_hash(scheme, &l.path, &l.meta, &l.file_hash),
+            size: if l.mutable { None } else { Some(l.size) },
+            mode: l.meta.mode,
+            mutable: l.mutable,
+        })
+        .collect();
+    let root_hash = MerkleTree::from_leaves(files.iter().map(|e| e.leaf).collect()).root();
+
+    LockManifest {
+        version: lock::LOCK_VERSION,
+        scheme,
+        preset: options.preset,
+        canonical: options.canonical,
+        root_hash,
+        files,
     }
-    Ok(())
 }
 
-fn load_mutable_patterns(root: &Path) -> Vec<String> {
-    let config_path = root.join(".openseal_mutable");
-    if let Ok(content) = fs::read_to_string(config_path) {
-        content.lines()
-            .map(|l| l.trim().to_string())
-            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+/// Computes the lock manifest, excluding specified directories.
+pub fn compute_lock_manifest_excluding(root_path: &Path, exclude_dirs: &[&str]) -> IdentityResult<LockManifest> {
+    compute_lock_manifest_with_options(root_path, &IdentityOptions::excluding(exclude_dirs))
+}
+
+/// Computes the lock manifest using the default exclusion rules of `compute_project_identity`.
+pub fn compute_lock_manifest(root_path: &Path) -> IdentityResult<LockManifest> {
+    compute_lock_manifest_with_options(root_path, &IdentityOptions::detect(root_path))
+}
+
+fn build_merkle_tree(scheme: IdentityScheme, leaves: &[FileLeaf]) -> MerkleTree {
+    MerkleTree::from_leaves(
+        leaves.iter()
+            .map(|l| merkle::leaf_hash(scheme, &l.path, &l.meta, &l.file_hash))
             .collect()
-    } else {
-        Vec::new()
-    }
+    )
 }
 
-fn compute_file_hash(path: &Path) -> Result<Hash> {
-    let mut file = fs::File::open(path).with_context(|| format!("Failed to open file: {:?}", path))?;
+/// Scans a directory and computes its Merkle Root hash.
+/// The exclusion preset is detected from marker files (`package.json`, `Cargo.toml`, ...) and recorded in the identity.
+pub fn compute_project_identity(root_path: &Path) -> IdentityResult<ProjectIdentity> {
+    compute_project_identity_with_options(root_path, &IdentityOptions::detect(root_path))
+}
+
+/// Files at least this large are hashed on the rayon pool with BLAKE3's tree mode.
+/// Below it, splitting the work costs more than it saves.
+pub const PARALLEL_HASH_THRESHOLD: u64 = 4 * 1024 * 1024;
+
+/// Read size for large writable files: big enough to keep every rayon thread busy per call.
+const PARALLEL_HASH_CHUNK: usize = 16 * 1024 * 1024;
+
+/// BLAKE3 of a file's content. Small files go through a 64 KiB buffer on the calling thread;
+/// large ones are hashed in parallel, with the same result.
+///
+/// A file truncated while it is mapped turns the next access into SIGBUS, so only files without
+/// any write bit (sealed read-only) are memory-mapped. Large writable files (e.g. checked-out model
+/// weights) are read in `PARALLEL_HASH_CHUNK` pieces, each hashed in parallel.
+pub fn compute_file_hash(path: &Path) -> IdentityResult<Hash> {
+    let mut file = fs::File::open(path).map_err(IdentityError::io("open", path))?;
+    let metadata = file.metadata().map_err(IdentityError::io("stat", path))?;
     let mut hasher = blake3::Hasher::new();
-    let mut buffer = [0; 65536];
-    loop {
-        let count = file.read(&mut buffer).context("Failed to read file")?;
-        if count == 0 { break; }
-        hasher.update(&buffer[..count]);
+    if metadata.len() < PARALLEL_HASH_THRESHOLD {
+        let mut buffer = [0; 65536];
+        loop {
+            let count = file.read(&mut buffer).map_err(IdentityError::io("read", path))?;
+            if count == 0 { break; }
+            hasher.update(&buffer[..count]);
+        }
+    } else if metadata.permissions().readonly() {
+        // Map the handle that was checked, not the path, so the file can't be swapped in between.
+        // SAFETY: the file is read-only (see above), so the mapped bytes don't change under us.
+        let map = unsafe { memmap2::Mmap::map(&file) }.map_err(IdentityError::io("map", path))?;
+        hasher.update_rayon(&map);
+    } else {
+        let mut buffer = vec![0; PARALLEL_HASH_CHUNK];
+        loop {
+            let count = read_full(&mut file, &mut buffer).map_err(IdentityError::io("read", path))?;
+            if count == 0 { break; }
+            hasher.update_rayon(&buffer[..count]);
+        }
     }
     Ok(hasher.finalize())
 }
 
+/// Fills `buffer` unless the end of the file comes first; returns the bytes read.
+fn read_full(file: &mut fs::File, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match file.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(count) => filled += count,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
 fn compute_mutable_file_hash(rel_path: &Path) -> Hash {
     let mut hasher = blake3::Hasher::new();
     hasher.update(b"MUTABLE_MARKER");
@@ -163,17 +639,6 @@ fn compute_mutable_file_hash(rel_path: &Path) -> Hash {
     hasher.finalize()
 }
 
-fn compute_merkle_root(hashes: &[Hash]) -> Hash {
-    if hashes.is_empty() {
-        return blake3::hash(b"EMPTY_PROJECT");
-    }
-    let mut hasher = blake3::Hasher::new();
-    for h in hashes {
-        hasher.update(h.as_bytes());
-    }
-    hasher.finalize()
-}
-
 // --- Phase 2: Internalized Pipeline (Sealing Logic) ---
 
 /// Determines what information is included in the Seal based on the environment.
@@ -200,6 +665,12 @@ impl SealMode {
 /// In Production mode, only `signature` is populated; other fields are None.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Seal {
+    /// Envelope version; absent (V1) in seals of runtimes that predate it.
+    #[serde(default)]
+    pub protocol: SealProtocol,
+    /// Signature scheme and hash function; absent in V1 seals, which are all Ed25519 + BLAKE3.
+    #[serde(default)]
+    pub suite: SealSuite,
     pub signature: String,            // Always present (Mandatory)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pub_key: Option<String>,      // Dev only: Ephemeral Public Key
@@ -207,14 +678,39 @@ pub struct Seal {
     pub a_hash: Option<String>,       // Dev only: Blinded Identity
     #[serde(skip_serializing_if = "Option::is_none")]
     pub b_hash: Option<String>,       // Dev only: Result Binding
+    /// Last time the runtime re-hashed the project and found it intact (RFC 3339, signed).
+    /// Absent in seals of runtimes that predate re-attestation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attested_at: Option<String>,
+    /// When the runtime issued the seal (RFC 3339, signed; V2 seals).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issued_at: Option<String>,
+    /// When the seal stops being valid (RFC 3339, signed), if the runtime sets a lifetime.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    /// `RequestDescription::digest` of the request this seal answers (hex, signed).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_digest: Option<String>,
+    /// Status code of the upstream response (signed); the runtime answers with it too.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Upstream response headers selected by `OPENSEAL_SEALED_HEADERS` (signed).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<SealedHeaders>,
 }
 
-/// Generates the Blinded A-hash (Execution Commitment).
-/// A = Hash(ProjectRoot || Wax)
+/// Generates the Blinded A-hash (Execution Commitment) of a seal in `protocol`.
+/// V1: A = Hash(ProjectRoot || Wax)
+/// V2: A = Hash(Algorithm || ":" || ProjectRoot || Wax)
 /// This binds the static identity to the dynamic request, and hides the raw Root Hash.
-pub fn compute_a_hash(project_root: &Hash, wax: &str) -> Hash {
+/// The V2 algorithm tag keeps a `sha256:` and a `blake3:` root with equal bytes apart.
+pub fn compute_a_hash(project_root: &Digest, wax: &str, protocol: SealProtocol) -> Hash {
     let mut hasher = blake3::Hasher::new();
     hasher.update(b"OPENSEAL_BLINDED_IDENTITY");
+    if protocol != SealProtocol::V1 {
+        hasher.update(project_root.algorithm().name().as_bytes());
+        hasher.update(b":");
+    }
     hasher.update(project_root.as_bytes());
     hasher.update(wax.as_bytes());
     hasher.finalize()
@@ -236,61 +732,204 @@ pub struct VerificationReport {
     pub signature_verified: bool,
     pub binding_verified: bool,
     pub identity_verified: bool,
+    /// Whether the seal answers `VerifyOptions.request` (true when no request was given).
+    pub request_verified: bool,
+    /// Whether the sealed status and headers match `VerifyOptions.status` / `headers` (true when none were given).
+    pub response_verified: bool,
+    /// Signed time of the runtime's last successful re-attestation, if the seal carries one.
+    pub attested_at: Option<String>,
+    /// Signed issuance time (V2 seals).
+    pub issued_at: Option<String>,
+    /// Signed expiry time, if the runtime sets one.
+    pub expires_at: Option<String>,
+    /// Signed request digest (V2 seals).
+    pub request_digest: Option<String>,
+    /// Signed upstream status code and headers (V2 seals).
+    pub status: Option<u16>,
+    pub headers: Option<SealedHeaders>,
+    pub protocol: SealProtocol,
+    pub suite: SealSuite,
+    pub failure: Option<VerificationFailure>,
     pub message: String,
 }
 
-/// Verifies the integrity of a Seal provided in a JSON response.
+impl VerificationReport {
+    fn fail(mut self, failure: VerificationFailure, message: impl Into<String>) -> Self {
+        self.valid = false;
+        self.failure = Some(failure);
+        self.message = message.into();
+        self
+    }
+}
+
+/// Why `verify_seal` rejected a seal whose envelope it could read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationFailure {
+    /// The signature doesn't cover these fields: tampered, or signed by another key.
+    InvalidSignature,
+    /// The A-hash doesn't match the expected root hash.
+    IdentityMismatch,
+    /// `expires_at` is at or before the verification time.
+    Expired,
+    /// `issued_at` is after the verification time (beyond the allowed clock skew).
+    FutureDated,
+    /// `issued_at` is older than the maximum age.
+    TooOld,
+    /// A maximum age is required but the seal carries no signed `issued_at` (V1 seals).
+    Undated,
+    /// The seal answers another request, or isn't bound to a request (V1 seals).
+    RequestMismatch,
+    /// The sealed status or headers differ from the expected ones, or aren't sealed.
+    ResponseMismatch,
+}
+
+/// What `verify_seal_with_options` checks besides the signature and the identity.
+#[derive(Debug, Clone)]
+pub struct VerifyOptions {
+    /// The time the seal is checked as of; set it to check an archived response at a past time.
+    pub at: DateTime<Utc>,
+    /// Seals issued longer ago than this fail (`TooOld`), as do seals without `issued_at` (`Undated`).
+    pub max_age: Option<chrono::Duration>,
+    /// Tolerated clock difference between the runtime and the verifier.
+    pub clock_skew: chrono::Duration,
+    /// The request the caller sent; the seal must be bound to it.
+    pub request: Option<RequestDescription>,
+    /// The HTTP status the caller received; the sealed upstream status must equal it.
+    pub status: Option<u16>,
+    /// Header values the sealed headers must have (`None`: sealed as absent). Names are case-insensitive.
+    pub headers: SealedHeaders,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        VerifyOptions {
+            at: Utc::now(),
+            max_age: None,
+            clock_skew: chrono::Duration::seconds(60),
+            request: None,
+            status: None,
+            headers: SealedHeaders::new(),
+        }
+    }
+}
+
+/// Verifies the integrity of a Seal provided in a JSON response, as of now and without a maximum age.
 /// 
 /// # Arguments
 /// * `response` - The full JSON response object (containing "result" and "openseal")
 /// * `wax` - The challenge string used for the request
-/// * `expected_root_hash` - Optional. If provided, verifies A-hash matches.
-pub fn verify_seal(response: &serde_json::Value, wax: &str, expected_root_hash: Option<&str>) -> Result<VerificationReport> {
+/// * `expected_root_hash` - Optional. If provided, verifies A-hash matches (for its algorithm).
+pub fn verify_seal(response: &serde_json::Value, wax: &str, expected_root_hash: Option<&Digest>) -> Result<VerificationReport> {
+    verify_seal_with_options(response, wax, expected_root_hash, &VerifyOptions::default())
+}
+
+/// `verify_seal` with the verification time, maximum age, request and response given by `options`.
+/// Expired, future-dated, too old, misdirected and mismatched seals are reported with their own `VerificationFailure`.
+pub fn verify_seal_with_options(
+    response: &serde_json::Value,
+    wax: &str,
+    expected_root_hash: Option<&Digest>,
+    options: &VerifyOptions,
+) -> Result<VerificationReport> {
     use ed25519_dalek::{Verifier, VerifyingKey, Signature};
 
     // 1. Extract Seal Components
     let openseal = response.get("openseal").context("Missing 'openseal' field")?;
     let result_val = response.get("result").context("Missing 'result' field")?;
+
+    // The envelope version decides which fields are signed and how; unknown ones are refused
+    let protocol = SealProtocol::from_envelope(openseal)?;
+    let suite = SealSuite::from_envelope(openseal, protocol)?;
     
     // In Dev mode, these should be present. In Prod, they are missing (cannot verify without out-of-band info).
     let signature_hex = openseal.get("signature").and_then(|v| v.as_str()).context("Missing signature")?;
     let pub_key_hex = openseal.get("pub_key").and_then(|v| v.as_str()).context("Missing pub_key (Dev mode required for CLI verify)")?;
     let a_hash_hex = openseal.get("a_hash").and_then(|v| v.as_str()).context("Missing a_hash")?;
     let b_hash_hex = openseal.get("b_hash").and_then(|v| v.as_str()).context("Missing b_hash")?;
+    // V1 doesn't sign these: an attested_at or request_digest next to a V1 signature proves nothing
+    let signed_value = |field: &str| match protocol {
+        SealProtocol::V1 => None,
+        _ => openseal.get(field),
+    };
+    let signed_field = |field: &str| signed_value(field).and_then(|v| v.as_str());
+    let attested_at = signed_field("attested_at");
+    let issued_at = signed_field("issued_at");
+    let expires_at = signed_field("expires_at");
+    let request_digest = signed_field("request_digest");
+    let status = signed_value("status")
+        .map(|v| v.as_u64().and_then(|code| u16::try_from(code).ok()).context("Invalid status"))
+        .transpose()?;
+    let headers = signed_value("headers")
+        .map(|v| SealedHeaders::deserialize(v).context("Invalid headers"))
+        .transpose()?;
 
     // 2. Decode Hex
-    let pub_key_bytes = hex::decode(pub_key_hex).context("Invalid pub_key hex")?;
-    let pub_key = VerifyingKey::from_bytes(&pub_key_bytes.try_into().map_err(|_| anyhow::anyhow!("Invalid pub_key length"))?)?;
-    
-    let signature_bytes = hex::decode(signature_hex).context("Invalid signature hex")?;
-    let signature = Signature::from_bytes(&signature_bytes.try_into().map_err(|_| anyhow::anyhow!("Invalid signature length"))?);
+    let signature_verified = match suite {
+        SealSuite::Ed25519Blake3 => {
+            let pub_key_bytes = hex::decode(pub_key_hex).context("Invalid pub_key hex")?;
+            let pub_key = VerifyingKey::from_bytes(&pub_key_bytes.try_into().map_err(|_| anyhow::anyhow!("Invalid pub_key length"))?)?;
 
-    // 3. Reconstruct Payload for Signature Verification
-    // Payload Rule: wax_hex + a_hash_hex + b_hash_hex + blake3(result_bytes).to_hex()
-    
-    // Issue: How to get original result bytes?
-    // We assume result_val is exact representation.
-    // Try converting back to string. Note: formatting differences will cause failure.
-    let result_str = if result_val.is_string() {
-        result_val.as_str().unwrap().to_string()
-    } else {
-        serde_json::to_string(result_val)?
+            let signature_bytes = hex::decode(signature_hex).context("Invalid signature hex")?;
+            let signature = Signature::from_bytes(&signature_bytes.try_into().map_err(|_| anyhow::anyhow!("Invalid signature length"))?);
+
+            // 3. Reconstruct Payload for Signature Verification
+            // The runtime hashes the result as re-serialized JSON (a non-JSON body is a JSON string),
+            // so formatting differences in the response will cause failure.
+            let result_str = serde_json::to_string(result_val)?;
+            let result_hash = suite.result_hash(result_str.as_bytes());
+            let payload = SealPayload {
+                wax,
+                a_hash: a_hash_hex,
+                b_hash: b_hash_hex,
+                result_hash: &result_hash,
+                attested_at,
+                issued_at,
+                expires_at,
+                request_digest,
+                status,
+                headers: headers.as_ref(),
+            };
+
+            // 4. Verify Signature
+            pub_key.verify(&payload.message(protocol, suite), &signature).is_ok()
+        }
+    };
+
+    let report = VerificationReport {
+        valid: true,
+        signature_verified: true,
+        binding_verified: true,
+        identity_verified: true,
+        request_verified: true,
+        response_verified: true,
+        attested_at: attested_at.map(str::to_string),
+        issued_at: issued_at.map(str::to_string),
+        expires_at: expires_at.map(str::to_string),
+        request_digest: request_digest.map(str::to_string),
+        status,
+        headers,
+        protocol,
+        suite,
+        failure: None,
+        message: "✅ SEAL VALID. The result is authentic and untampered.".to_string(),
     };
-    
-    let result_hash = blake3::hash(result_str.as_bytes()).to_hex().to_string();
-    let payload = format!("{}{}{}{}", wax, a_hash_hex, b_hash_hex, result_hash);
-    
-    // 4. Verify Signature
-    let signature_verified = pub_key.verify(payload.as_bytes(), &signature).is_ok();
     
     if !signature_verified {
         return Ok(VerificationReport {
-            valid: false,
             signature_verified: false,
             binding_verified: false,
             identity_verified: false,
-            message: "Signature verification failed. The seal may have been tampered.".to_string(),
-        });
+            request_verified: false,
+            response_verified: false,
+            attested_at: None,
+            issued_at: None,
+            expires_at: None,
+            request_digest: None,
+            status: None,
+            headers: None,
+            ..report
+        }.fail(VerificationFailure::InvalidSignature, "Signature verification failed. The seal may have been tampered."));
     }
 
     // 5. Verify Logic Binding (B-hash)
@@ -304,35 +943,94 @@ pub fn verify_seal(response: &serde_json::Value, wax: &str, expected_root_hash:
     
     // Legacy check removed: we don't have compute_b_hash here anymore.
 
-    // 6. Verify Identity (Optional A-hash check)
-    let mut identity_verified = true;
-    if let Some(root_hash_hex) = expected_root_hash {
-        let root_hash = Hash::from_hex(root_hash_hex)?;
+    // 6. Verify Validity Period (signed, so the times can be trusted now)
+    let parse_time = |field: &str, value: &str| {
+        DateTime::parse_from_rfc3339(value)
+            .map(|time| time.with_timezone(&Utc))
+            .with_context(|| format!("Invalid {} '{}' (expected RFC 3339)", field, value))
+    };
+    let issued = issued_at.map(|value| parse_time("issued_at", value)).transpose()?;
+    let expires = expires_at.map(|value| parse_time("expires_at", value)).transpose()?;
+    if let Some(issued) = issued {
+        if issued > options.at + options.clock_skew {
+            let message = format!("Seal is future-dated: issued at {}, after the verification time {}.", issued, options.at);
+            return Ok(report.fail(VerificationFailure::FutureDated, message));
+        }
+    }
+    if let Some(expires) = expires {
+        if expires <= options.at {
+            let message = format!("Seal expired at {} (verification time {}).", expires, options.at);
+            return Ok(report.fail(VerificationFailure::Expired, message));
+        }
+    }
+    if let Some(max_age) = options.max_age {
+        match issued {
+            None => {
+                let message = "Seal carries no signed issuance time, so its age cannot be checked.";
+                return Ok(report.fail(VerificationFailure::Undated, message));
+            }
+            Some(issued) if options.at - issued > max_age => {
+                let message = format!("Seal is too old: issued at {}, more than {}s before {}.", issued, max_age.num_seconds(), options.at);
+                return Ok(report.fail(VerificationFailure::TooOld, message));
+            }
+            Some(_) => {}
+        }
+    }
+
+    // 7. Verify Request Binding (Optional, against the request the caller sent)
+    if let Some(request) = &options.request {
+        let expected = request.digest().to_hex().to_string();
+        if request_digest != Some(expected.as_str()) {
+            let message = match request_digest {
+                Some(_) => format!("Request Mismatch. This seal answers another request than {} {}.", request.method, request.path),
+                None => "Request Mismatch. This seal is not bound to a request.".to_string(),
+            };
+            return Ok(VerificationReport { request_verified: false, ..report }
+                .fail(VerificationFailure::RequestMismatch, message));
+        }
+    }
+
+    // 8. Verify Response Status and Headers (Optional, against the HTTP response the caller received)
+    if let Some(message) = response_mismatch(&report, options) {
+        return Ok(VerificationReport { response_verified: false, ..report }
+            .fail(VerificationFailure::ResponseMismatch, message));
+    }
+
+    // 9. Verify Identity (Optional A-hash check)
+    if let Some(root_hash) = expected_root_hash {
         // We can verify A-hash because compute_a_hash is PUBLIC spec.
-        let computed_a = compute_a_hash(&root_hash, wax);
+        let computed_a = compute_a_hash(root_hash, wax, protocol);
         if computed_a.to_hex().to_string() != a_hash_hex {
-            identity_verified = false;
+            return Ok(VerificationReport { identity_verified: false, ..report }
+                .fail(VerificationFailure::IdentityMismatch, "Identity Mismatch. The code executed is different from what was expected."));
         }
     }
-    
-    if expected_root_hash.is_some() && !identity_verified {
-        return Ok(VerificationReport {
-            valid: false,
-            signature_verified: true,
-            binding_verified: true,
-            identity_verified: false,
-            message: "Identity Mismatch. The code executed is different from what was expected.".to_string(),
-        });
-    }
 
-    Ok(VerificationReport {
-        valid: true,
-        signature_verified: true,
-        binding_verified: true,
-        identity_verified,
-        message: "✅ SEAL VALID. The result is authentic and untampered.".to_string(),
-    })
+    Ok(report)
+}
+/// Why the sealed status and headers don't match the ones expected by `options`, if they don't.
+fn response_mismatch(report: &VerificationReport, options: &VerifyOptions) -> Option<String> {
+    if let Some(expected) = options.status {
+        match report.status {
+            Some(status) if status == expected => {}
+            Some(status) => return Some(format!("Response Mismatch. The seal covers status {}, not {}.", status, expected)),
+            None => return Some("Response Mismatch. This seal doesn't cover the response status.".to_string()),
+        }
+    }
+    let show = |value: &Option<String>| value.clone().unwrap_or_else(|| "(absent)".to_string());
+    for (name, expected) in &options.headers {
+        let name = name.to_ascii_lowercase();
+        match report.headers.as_ref().and_then(|headers| headers.get(&name)) {
+            Some(sealed) if sealed == expected => {}
+            Some(sealed) => {
+                return Some(format!("Response Mismatch. The seal covers {}: {}, not {}.", name, show(sealed), show(expected)));
+            }
+            None => return Some(format!("Response Mismatch. Header {} is not sealed.", name)),
+        }
+    }
+    None
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -407,23 +1105,524 @@ mod tests {
     }
 
     #[test]
-    fn test_dynamic_b_hash_binding() {
-        let a_hash = blake3::hash(b"PROJECT_IDENTITY");
-        let wax1 = "WAX_1";
-        let wax2 = "WAX_2";
-        let result = b"Execution Result";
-
-        let b1 = compute_b_hash(&a_hash, wax1, result);
-        let b2 = compute_b_hash(&a_hash, wax2, result);
-        
-        // Different waxes should produce different B-hashes even if A and Result are same
-        assert_ne!(b1, b2);
+    fn test_inclusion_proof_for_project_file() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir(dir.path().join("src"))?;
+        for name in ["README.md", "src/main.rs", "src/lib.rs"] {
+            fs::write(dir.path().join(name), name)?;
+        }
+
+        let root = compute_project_identity(dir.path())?.root_hash.as_blake3().unwrap();
+        let proof = compute_inclusion_proof(dir.path(), "src/lib.rs")?;
+
+        assert_eq!(proof.leaf_count, 3);
+        assert!(verify_inclusion(&root, "src/lib.rs", &blake3::hash(b"src/lib.rs"), &proof));
+        assert!(!verify_inclusion(&root, "src/main.rs", &blake3::hash(b"src/lib.rs"), &proof));
+        assert!(compute_inclusion_proof(dir.path(), "missing.rs").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_changes_root_hash() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir_all(dir.path().join("a"))?;
+        fs::create_dir_all(dir.path().join("b"))?;
+        fs::write(dir.path().join("a/app.py"), "print(1)")?;
+        let identity1 = compute_project_identity(dir.path())?;
+
+        // Same content, same sort position, different directory
+        fs::rename(dir.path().join("a/app.py"), dir.path().join("b/app.py"))?;
+        let identity2 = compute_project_identity(dir.path())?;
+
+        assert_ne!(identity1.root_hash, identity2.root_hash);
+        assert_eq!(identity1.scheme, IdentityScheme::V2);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_executable_bit_is_scheme_versioned() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir()?;
+        let script = dir.path().join("run.sh");
+        fs::write(&script, "echo hi")?;
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o644))?;
+        let v1_before = compute_project_identity_with_scheme(dir.path(), &[], IdentityScheme::V1)?;
+        let v2_before = compute_project_identity(dir.path())?;
+
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+        let v1_after = compute_project_identity_with_scheme(dir.path(), &[], IdentityScheme::V1)?;
+        let v2_after = compute_project_identity(dir.path())?;
+
+        // V1 roots keep verifying; V2 pins the permission change
+        assert_eq!(v1_before.root_hash, v1_after.root_hash);
+        assert_ne!(v2_before.root_hash, v2_after.root_hash);
+
+        // Umask-only differences don't alter the identity
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o775))?;
+        assert_eq!(compute_project_identity(dir.path())?.root_hash, v2_after.root_hash);
+        Ok(())
+    }
+
+    #[test]
+    fn test_identity_without_scheme_deserializes_as_v1() -> Result<()> {
+        let json = serde_json::json!({
+            "root_hash": blake3::hash(b"old"),
+            "file_count": 1,
+            "mutable_files": []
+        });
+        let identity: ProjectIdentity = serde_json::from_value(json)?;
+        assert_eq!(identity.scheme, IdentityScheme::V1);
+        assert_eq!(identity.root_hash, Digest::blake3(blake3::hash(b"old")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_a_hash_binds_root_algorithm() {
+        let bytes = *blake3::hash(b"root").as_bytes();
+        let project = Digest::new(DigestAlgorithm::Blake3, bytes);
+        let image = Digest::new(DigestAlgorithm::Sha256, bytes);
+        let v2 = SealProtocol::V2;
+        assert_ne!(compute_a_hash(&project, "wax", v2), compute_a_hash(&image, "wax", v2));
+        assert_eq!(compute_a_hash(&image, "wax", v2), compute_a_hash(&image.to_string().parse().unwrap(), "wax", v2));
+
+        // V1 seals keep the untagged formula of earlier versions
+        let mut legacy = blake3::Hasher::new();
+        legacy.update(b"OPENSEAL_BLINDED_IDENTITY");
+        legacy.update(&bytes);
+        legacy.update(b"wax");
+        assert_eq!(compute_a_hash(&project, "wax", SealProtocol::V1), legacy.finalize());
+    }
+
+    #[test]
+    fn test_lock_manifest_roundtrip() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("main.py"), "print('sealed')")?;
+        fs::write(dir.path().join("state.db"), "runtime data")?;
+        fs::write(dir.path().join(".openseal_mutable"), "state.db\n")?;
+
+        let lock = compute_lock_manifest(dir.path())?;
+        let identity = compute_project_identity(dir.path())?;
+        assert_eq!(lock.identity(), identity);
+
+        // Writing the lock into the project must not change the identity
+        lock.save(&dir.path().join(LOCK_FILE_NAME))?;
+        assert_eq!(compute_project_identity(dir.path())?.root_hash, identity.root_hash);
+
+        let loaded = LockManifest::load(&dir.path().join(LOCK_FILE_NAME))?;
+        assert_eq!(loaded, lock);
+        assert_eq!(loaded.to_json_string()?, lock.to_json_string()?);
+
+        let db = loaded.files.iter().find(|e| e.path == "state.db").unwrap();
+        assert!(db.mutable && db.size.is_none());
+        let main = loaded.files.iter().find(|e| e.path == "main.py").unwrap();
+        assert_eq!(main.size, Some(15));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_manifest_roundtrip_with_sibling_names() -> Result<()> {
+        let dir = tempdir()?;
+        for path in ["a/b", "a-b/x", "lib/mod.rs", "lib.rs"] {
+            fs::create_dir_all(dir.path().join(path).parent().unwrap())?;
+            fs::write(dir.path().join(path), path)?;
+        }
+
+        // Component order, not string order: `a/b` before `a-b/x`, `lib/mod.rs` before `lib.rs`
+        let lock = compute_lock_manifest(dir.path())?;
+        let paths: Vec<&str> = lock.files.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["a/b", "a-b/x", "lib/mod.rs", "lib.rs"]);
+
+        lock.save(&dir.path().join(LOCK_FILE_NAME))?;
+        assert_eq!(LockManifest::load(&dir.path().join(LOCK_FILE_NAME))?, lock);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tampered_lock_manifest_is_rejected() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("a.txt"), "a")?;
+        fs::write(dir.path().join("b.txt"), "b")?;
+
+        let lock = compute_lock_manifest(dir.path())?;
+        let tampered = lock.to_json_string()?.replace(&lock.files[0].hash.to_hex().to_string(), &blake3::hash(b"x").to_hex());
+        assert!(LockManifest::from_json_str(&tampered).is_err());
+        Ok(())
+    }
+
+    /// Backdates a file so the hash cache considers it settled (outside the racy window).
+    fn backdate(path: &Path) -> Result<()> {
+        let past = std::time::SystemTime::now() - std::time::Duration::from_secs(60);
+        File::options().write(true).open(path)?.set_modified(past)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_cached_identity_is_bit_identical() -> Result<()> {
+        let dir = tempdir()?;
+        let cache_path = dir.path().join("cache.json");
+        let project = dir.path().join("project");
+        fs::create_dir_all(project.join("src"))?;
+        for name in ["src/a.rs", "src/b.rs", "README.md"] {
+            fs::write(project.join(name), name)?;
+            backdate(&project.join(name))?;
+        }
+        let full = compute_project_identity(&project)?;
+
+        // Cold cache: everything hashed
+        let mut cache = HashCache::load(&cache_path, &project);
+        assert_eq!(compute_project_identity_cached(&project, &IdentityOptions::detect(&project), &mut cache)?, full);
+        assert_eq!(cache.stats(), (0, 3));
+        cache.save()?;
+
+        // Warm cache from disk: everything reused
+        let mut cache = HashCache::load(&cache_path, &project);
+        assert_eq!(compute_project_identity_cached(&project, &IdentityOptions::detect(&project), &mut cache)?, full);
+        assert_eq!(cache.stats(), (3, 0));
+
+        // A changed file is re-hashed and the result still matches a full scan
+        fs::write(project.join("src/a.rs"), "changed!")?;
+        let cached = compute_project_identity_cached(&project, &IdentityOptions::detect(&project), &mut cache)?;
+        assert_eq!(cached, compute_project_identity(&project)?);
+        assert_ne!(cached.root_hash, full.root_hash);
+        assert_eq!(cache.stats(), (5, 1));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cache_is_saved_privately() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir()?;
+        let project = dir.path().join("project");
+        fs::create_dir_all(&project)?;
+        fs::write(project.join("main.py"), "print(1)")?;
+        backdate(&project.join("main.py"))?;
+
+        // A symlink planted at the cache path is replaced, not written through
+        let cache_dir = dir.path().join("cache/openseal");
+        let cache_path = cache_dir.join("hashcache.json");
+        let victim = dir.path().join("victim");
+        fs::write(&victim, "keep")?;
+        let mut cache = HashCache::load(&cache_path, &project);
+        compute_project_identity_cached(&project, &IdentityOptions::detect(&project), &mut cache)?;
+        cache.save()?;
+        assert_eq!(fs::metadata(&cache_dir)?.permissions().mode() & 0o777, 0o700);
+        fs::remove_file(&cache_path)?;
+        std::os::unix::fs::symlink(&victim, &cache_path)?;
+        cache.save()?;
+        assert_eq!(fs::read_to_string(&victim)?, "keep");
+        assert!(!fs::symlink_metadata(&cache_path)?.file_type().is_symlink());
+
+        // Strict caches are never written back
+        fs::remove_file(&cache_path)?;
+        cache.set_strict(true);
+        cache.save()?;
+        assert!(!cache_path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_strict_cache_ignores_poisoned_entries() -> Result<()> {
+        let dir = tempdir()?;
+        let file = dir.path().join("main.py");
+        fs::write(&file, "print('v1')")?;
+        backdate(&file)?;
+        let original = compute_project_identity(dir.path())?;
 
-        let a_hash_modified = blake3::hash(b"PROJECT_IDENTITY_MODIFIED");
-        let b3 = compute_b_hash(&a_hash_modified, wax1, result);
+        let mut cache = HashCache::in_memory();
+        compute_project_identity_cached(dir.path(), &IdentityOptions::detect(dir.path()), &mut cache)?;
 
-        // Different A-hash should produce different B-hashes
-        assert_ne!(b1, b3);
+        // Same size, restored mtime: the cache cannot see the change, strict mode can
+        let mtime = fs::metadata(&file)?.modified()?;
+        fs::write(&file, "print('v2')")?;
+        File::options().write(true).open(&file)?.set_modified(mtime)?;
+
+        let lenient = compute_project_identity_cached(dir.path(), &IdentityOptions::detect(dir.path()), &mut cache)?;
+        assert_eq!(lenient.root_hash, original.root_hash);
+
+        cache.set_strict(true);
+        let strict = compute_project_identity_cached(dir.path(), &IdentityOptions::detect(dir.path()), &mut cache)?;
+        assert_eq!(strict, compute_project_identity(dir.path())?);
+        assert_ne!(strict.root_hash, original.root_hash);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mutable_patterns_do_not_match_by_suffix() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir_all(dir.path().join("logs"))?;
+        fs::write(dir.path().join("db"), "mutable")?;
+        fs::write(dir.path().join("mydb"), "sealed")?;
+        fs::write(dir.path().join("logs/audit.db"), "sealed")?;
+        fs::write(dir.path().join(".openseal_mutable"), "db\n")?;
+
+        let identity = compute_project_identity(dir.path())?;
+        assert_eq!(identity.mutable_files, vec!["db"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_policy_file_is_sealed_and_enforced() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir_all(dir.path().join("data"))?;
+        fs::write(dir.path().join("data/prices.json"), "{}")?;
+        fs::write(dir.path().join("data/feed.conf"), "interval=5")?;
+        fs::write(dir.path().join(".openseal_mutable"), "data/\n")?;
+
+        // JSON data may be mutable, configuration is denied by default
+        let err = compute_project_identity(dir.path()).unwrap_err();
+        assert!(matches!(
+            err,
+            IdentityError::Policy(PolicyViolation::DeniedPattern { ref path, .. }) if path == "data/feed.conf"
+        ));
+
+        fs::write(dir.path().join(POLICY_FILE_NAME), r#"{ "allow": ["data/*.conf"] }"#)?;
+        let allowed = compute_project_identity(dir.path())?;
+        assert_eq!(allowed.mutable_files, vec!["data/feed.conf", "data/prices.json"]);
+
+        // Loosening the policy changes the sealed identity
+        fs::write(dir.path().join(POLICY_FILE_NAME), r#"{ "allow": ["data/**"] }"#)?;
+        assert_ne!(compute_project_identity(dir.path())?.root_hash, allowed.root_hash);
+
+        // An unsealed policy is rejected
+        fs::write(dir.path().join(".opensealignore"), ".openseal_policy.json\n")?;
+        assert!(compute_project_identity(dir.path()).is_err());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_policies() -> Result<()> {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempdir()?;
+        fs::write(dir.path().join("config.txt"), "v1")?;
+        symlink("config.txt", dir.path().join("current"))?;
+
+        let with = |symlinks| IdentityOptions { symlinks, ..Default::default() };
+
+        // HashTarget: the link is a leaf committing to its target path
+        let hashed = compute_project_identity_with_options(dir.path(), &with(SymlinkPolicy::HashTarget))?;
+        assert_eq!(hashed.file_count, 2);
+        let lock = compute_lock_manifest_with_options(dir.path(), &with(SymlinkPolicy::HashTarget))?;
+        assert_eq!(lock.files[1].mode, LeafMeta::SYMLINK.mode);
+
+        // Retargeting the link changes the identity
+        fs::write(dir.path().join("other.txt"), "v1")?;
+        let before = compute_project_identity_with_options(dir.path(), &with(SymlinkPolicy::HashTarget))?;
+        fs::remove_file(dir.path().join("current"))?;
+        symlink("other.txt", dir.path().join("current"))?;
+        let after = compute_project_identity_with_options(dir.path(), &with(SymlinkPolicy::HashTarget))?;
+        assert_ne!(before.root_hash, after.root_hash);
+
+        // Follow: the link is sealed as a regular file with the target's content
+        let followed = compute_lock_manifest_with_options(dir.path(), &with(SymlinkPolicy::Follow))?;
+        let current = followed.files.iter().find(|e| e.path == "current").unwrap();
+        assert_eq!(current.hash, blake3::hash(b"v1"));
+        assert_eq!(current.mode, LeafMeta::REGULAR.mode);
+
+        // Reject: any link aborts
+        assert!(matches!(
+            compute_project_identity_with_options(dir.path(), &with(SymlinkPolicy::Reject)),
+            Err(IdentityError::SymlinkRejected { .. })
+        ));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_escaping_symlinks_always_detected() -> Result<()> {
+        use std::os::unix::fs::symlink;
+
+        let outside = tempdir()?;
+        fs::write(outside.path().join("secret.txt"), "outside")?;
+        let dir = tempdir()?;
+        fs::write(dir.path().join("main.py"), "print(1)")?;
+        symlink(outside.path(), dir.path().join("node_modules"))?;
+
+        for policy in [SymlinkPolicy::HashTarget, SymlinkPolicy::Follow, SymlinkPolicy::Reject] {
+            let options = IdentityOptions { symlinks: policy, ..Default::default() };
+            let result = compute_project_identity_with_options(dir.path(), &options);
+            assert!(matches!(result, Err(IdentityError::SymlinkEscapes { .. } | IdentityError::SymlinkRejected { .. })), "{:?}", policy);
+        }
+
+        // Explicitly excluded links are not part of the identity at all
+        let excluded = compute_project_identity_excluding(dir.path(), &["node_modules"])?;
+        assert_eq!(excluded.file_count, 1);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_strict_mode_aborts_on_skipped_entries() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("main.py"), "print(1)")?;
+        // A link loop the walker cannot descend into
+        std::os::unix::fs::symlink(".", dir.path().join("loop"))?;
+        let mut options = IdentityOptions { symlinks: SymlinkPolicy::Follow, ..Default::default() };
+
+        let err = compute_project_identity_with_options(dir.path(), &options).unwrap_err();
+        assert!(matches!(err, IdentityError::Walk { .. }), "{}", err);
+        assert_eq!(err.path(), Some(dir.path().join("loop").as_path()));
+
+        options.strict = false;
+        let (identity, explanation) = compute_project_identity_explained(dir.path(), &options)?;
+        assert_eq!(identity.file_count, 1);
+        assert_eq!(explanation.get("loop").map(|e| e.status), Some(EntryStatus::Skipped));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_special_files_are_skipped_or_rejected() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("main.py"), "print(1)")?;
+        let _listener = std::os::unix::net::UnixListener::bind(dir.path().join("app.sock"))?;
+
+        let err = compute_project_identity_with_options(dir.path(), &IdentityOptions::default()).unwrap_err();
+        assert!(matches!(err, IdentityError::SpecialFile { kind: "socket", .. }), "{}", err);
+        assert_eq!(err.path(), Some(dir.path().join("app.sock").as_path()));
+
+        let options = IdentityOptions { strict: false, ..Default::default() };
+        let (identity, explanation) = compute_project_identity_explained(dir.path(), &options)?;
+        assert_eq!(identity.file_count, 1);
+        let skipped = explanation.get("app.sock").expect("socket is reported");
+        assert_eq!(skipped.status, EntryStatus::Skipped);
+        assert_eq!(skipped.reason, "socket (no content to seal)");
+        Ok(())
+    }
+
+    #[test]
+    fn test_detected_preset_excludes_by_products() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("package.json"), "{}")?;
+        fs::write(dir.path().join("index.js"), "console.log(1)")?;
+        let clean = compute_project_identity(dir.path())?;
+        assert_eq!(clean.preset, Some(LanguagePreset::Node));
+
+        fs::create_dir_all(dir.path().join(".git/objects"))?;
+        fs::write(dir.path().join(".git/HEAD"), "ref: refs/heads/main")?;
+        fs::create_dir_all(dir.path().join("lib/node_modules/dep"))?;
+        fs::write(dir.path().join("lib/node_modules/dep/index.js"), "x")?;
+        assert_eq!(compute_project_identity(dir.path())?, clean);
+
+        // Identities recorded without a preset are reproduced without exclusions
+        let legacy = compute_project_identity_with_options(dir.path(), &IdentityOptions::default())?;
+        assert_eq!(legacy.file_count, 4);
+        assert_eq!(legacy.preset, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_preset_keeps_nested_build_dirs_sealed() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("pom.xml"), "<project/>")?;
+        fs::create_dir_all(dir.path().join("src/main/java/build"))?;
+        fs::write(dir.path().join("src/main/java/build/Builder.java"), "class Builder {}")?;
+        let clean = compute_project_identity(dir.path())?;
+        assert_eq!(clean.preset, Some(LanguagePreset::Java));
+        assert_eq!(clean.file_count, 2);
+
+        // Only the root-level build output is a by-product
+        fs::create_dir_all(dir.path().join("build/classes"))?;
+        fs::write(dir.path().join("build/classes/Builder.class"), "cafebabe")?;
+        assert_eq!(compute_project_identity(dir.path())?, clean);
+
+        fs::write(dir.path().join("src/main/java/build/Builder.java"), "class Builder { int x; }")?;
+        assert_ne!(compute_project_identity(dir.path())?.root_hash, clean.root_hash);
+        Ok(())
+    }
+
+    #[test]
+    fn test_large_files_hash_like_small_ones() -> Result<()> {
+        let dir = tempdir()?;
+        let content: Vec<u8> = (0..PARALLEL_HASH_CHUNK + 12345).map(|i| (i % 251) as u8).collect();
+        fs::write(dir.path().join("weights.bin"), &content)?;
+        fs::write(dir.path().join("sealed.bin"), &content)?;
+        let mut read_only = fs::metadata(dir.path().join("sealed.bin"))?.permissions();
+        read_only.set_readonly(true);
+        fs::set_permissions(dir.path().join("sealed.bin"), read_only)?;
+        fs::write(dir.path().join("small.bin"), &content[..1000])?;
+        // Writable files are read in chunks, read-only ones mapped
+        assert_eq!(compute_file_hash(&dir.path().join("weights.bin"))?, blake3::hash(&content));
+        assert_eq!(compute_file_hash(&dir.path().join("sealed.bin"))?, blake3::hash(&content));
+        assert_eq!(compute_file_hash(&dir.path().join("small.bin"))?, blake3::hash(&content[..1000]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_reports_progress_and_can_be_cancelled() -> Result<()> {
+        use std::sync::{Arc, Mutex};
+        let dir = tempdir()?;
+        fs::write(dir.path().join("a.js"), "12345")?;
+        fs::write(dir.path().join("b.js"), "678")?;
+
+        let last = Arc::new(Mutex::new(ScanProgress::default()));
+        let seen = Arc::clone(&last);
+        let options = IdentityOptions {
+            progress: Some(ProgressCallback::new(move |progress| *seen.lock().unwrap() = progress)),
+            ..Default::default()
+        };
+        let identity = compute_project_identity_with_options(dir.path(), &options)?;
+        assert_eq!(identity, compute_project_identity_with_options(dir.path(), &IdentityOptions::default())?);
+        assert_eq!(*last.lock().unwrap(), ScanProgress {
+            files_discovered: 2,
+            discovery_complete: true,
+            files_hashed: 2,
+            bytes_hashed: 8,
+        });
+
+        let options = IdentityOptions::default();
+        options.cancel.cancel();
+        assert!(matches!(compute_project_identity_with_options(dir.path(), &options), Err(IdentityError::Cancelled)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_portable_text_profile_matches_across_checkouts() -> Result<()> {
+        // A Windows checkout (CRLF) and a macOS one (NFD names) of the same files
+        let windows = tempdir()?;
+        let mac = tempdir()?;
+        fs::create_dir_all(windows.path().join("src"))?;
+        fs::create_dir_all(mac.path().join("src"))?;
+        fs::write(windows.path().join("src/caf\u{e9}.js"), "a();\r\nb();\r\n")?;
+        fs::write(mac.path().join("src/cafe\u{301}.js"), "a();\nb();\n")?;
+        fs::write(windows.path().join("logo.bin"), b"\0\r\n")?;
+        fs::write(mac.path().join("logo.bin"), b"\0\r\n")?;
+
+        let options = IdentityOptions { canonical: CanonicalProfile::PortableText, ..Default::default() };
+        let portable = compute_project_identity_with_options(windows.path(), &options)?;
+        assert_eq!(portable, compute_project_identity_with_options(mac.path(), &options)?);
+        assert_eq!(portable.canonical, CanonicalProfile::PortableText);
+        assert_eq!(compute_lock_manifest_with_options(mac.path(), &options)?.identity(), portable);
+
+        // The native profile keeps the bytes, and stays out of serialized identities
+        let native = compute_project_identity_with_options(windows.path(), &IdentityOptions::default())?;
+        assert_ne!(native, compute_project_identity_with_options(mac.path(), &IdentityOptions::default())?);
+        assert!(serde_json::to_value(&native)?.get("canonical").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_directory_diffs_against_a_lock_with_its_options() -> Result<()> {
+        let sealed = tempdir()?;
+        let checkout = tempdir()?;
+        fs::write(sealed.path().join("main.py"), "a()\n")?;
+        fs::write(checkout.path().join("main.py"), "a()\r\n")?;
+        let options = IdentityOptions { canonical: CanonicalProfile::PortableText, ..IdentityOptions::detect(sealed.path()) };
+        let lock = compute_lock_manifest_with_options(sealed.path(), &options)?;
+
+        // The detected native options see a modified file; the lock's own options see none
+        let native = compute_lock_manifest(checkout.path())?;
+        assert_eq!(diff_manifests(&lock, &native).modified, ["main.py"]);
+        assert_eq!(lock.options().canonical, CanonicalProfile::PortableText);
+        assert_eq!(lock.options().preset, options.preset);
+        let live = compute_lock_manifest_with_options(checkout.path(), &lock.options())?;
+        assert!(diff_manifests(&lock, &live).is_empty());
+        Ok(())
     }
 
     // SealMode tests
@@ -448,10 +1647,18 @@ mod tests {
         #[test]
         fn test_seal_serialization_full() {
             let seal = Seal {
+                protocol: SealProtocol::CURRENT,
+                suite: SealSuite::CURRENT,
                 signature: "abc123".to_string(),
                 pub_key: Some("key123".to_string()),
                 a_hash: Some("ahash123".to_string()),
                 b_hash: Some("bhash123".to_string()),
+                attested_at: Some("2026-01-22T10:00:00Z".to_string()),
+                issued_at: Some("2026-01-22T10:05:00Z".to_string()),
+                expires_at: None,
+                request_digest: None,
+                status: None,
+                headers: None,
             };
             
             let json = serde_json::to_string(&seal).unwrap();
@@ -462,10 +1669,18 @@ mod tests {
         #[test]
         fn test_seal_serialization_signature_only() {
             let seal = Seal {
+                protocol: SealProtocol::CURRENT,
+                suite: SealSuite::CURRENT,
                 signature: "abc123".to_string(),
                 pub_key: None,
                 a_hash: None,
                 b_hash: None,
+                attested_at: None,
+                issued_at: None,
+                expires_at: None,
+                request_digest: None,
+                status: None,
+                headers: None,
             };
             
             let json = serde_json::to_string(&seal).unwrap();
@@ -0,0 +1,161 @@
+use anyhow::{Context, Result};
+use blake3::Hash;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::merkle::{self, FileKind, IdentityScheme, LeafMeta, MerkleTree};
+use crate::preset::LanguagePreset;
+use crate::canonical::CanonicalProfile;
+use crate::{Digest, IdentityOptions, ProjectIdentity};
+
+/// File name of the lock manifest written next to the project root.
+/// It is never part of the identity it describes.
+pub const LOCK_FILE_NAME: &str = "openseal.lock";
+
+/// Current lock manifest format version.
+pub const LOCK_VERSION: u32 = 1;
+
+/// Deterministic listing of every file that makes up a `ProjectIdentity`.
+/// Entries are sorted by path (component by component), so the serialized form is stable and diffable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockManifest {
+    pub version: u32,
+    pub scheme: IdentityScheme,
+    /// Exclusion preset the file set was selected with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<LanguagePreset>,
+    /// Path and line-ending normalization the hashes were computed with.
+    #[serde(default, skip_serializing_if = "CanonicalProfile::is_native")]
+    pub canonical: CanonicalProfile,
+    #[serde(with = "hex_hash")]
+    pub root_hash: Hash,
+    pub files: Vec<LockEntry>,
+}
+
+/// One file of the sealed project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockEntry {
+    pub path: String,
+    /// Content hash (or mutable marker hash for mutable files).
+    #[serde(with = "hex_hash")]
+    pub hash: Hash,
+    /// Merkle leaf committed to by the root.
+    #[serde(with = "hex_hash")]
+    pub leaf: Hash,
+    /// Size in bytes. Omitted for mutable files, whose content is not sealed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Normalized mode as an octal string (e.g. "100644").
+    #[serde(with = "octal_mode")]
+    pub mode: u32,
+    #[serde(default)]
+    pub mutable: bool,
+}
+
+impl LockEntry {
+    pub fn meta(&self) -> LeafMeta {
+        let kind = if self.mode == LeafMeta::SYMLINK.mode { FileKind::Symlink } else { FileKind::File };
+        LeafMeta { kind, mode: self.mode }
+    }
+}
+
+impl LockManifest {
+    /// Parses a lock manifest and checks that its entries reproduce the recorded root.
+    pub fn from_json_str(content: &str) -> Result<Self> {
+        let manifest: LockManifest = serde_json::from_str(content).context("Invalid lock manifest")?;
+        if manifest.version != LOCK_VERSION {
+            anyhow::bail!("Unsupported lock manifest version: {}", manifest.version);
+        }
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read lock manifest: {:?}", path))?;
+        Self::from_json_str(&content)
+    }
+
+    /// Serializes to pretty JSON with a trailing newline (stable across runs).
+    pub fn to_json_string(&self) -> Result<String> {
+        let mut out = serde_json::to_string_pretty(self)?;
+        out.push('\n');
+        Ok(out)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_json_string()?)
+            .with_context(|| format!("Failed to write lock manifest: {:?}", path))
+    }
+
+    /// Options that select and hash a directory the way this manifest was computed
+    /// (recorded scheme, preset and canonical profile), for diffing it against a live tree.
+    pub fn options(&self) -> IdentityOptions {
+        IdentityOptions { scheme: self.scheme, preset: self.preset, canonical: self.canonical, ..Default::default() }
+    }
+
+    /// Recomputes every leaf and the root from the entries.
+    pub fn validate(&self) -> Result<()> {
+        // Same order as the scanners: by path component, so `a/b` comes before `a-b`
+        for pair in self.files.windows(2) {
+            if Path::new(&pair[0].path) >= Path::new(&pair[1].path) {
+                anyhow::bail!("Lock manifest entries are not sorted or contain duplicates: {}", pair[1].path);
+            }
+        }
+        for entry in &self.files {
+            let leaf = merkle::leaf_hash(self.scheme, &entry.path, &entry.meta(), &entry.hash);
+            if leaf != entry.leaf {
+                anyhow::bail!("Lock manifest leaf mismatch for {}", entry.path);
+            }
+        }
+        let root = MerkleTree::from_leaves(self.files.iter().map(|e| e.leaf).collect()).root();
+        if root != self.root_hash {
+            anyhow::bail!(
+                "Lock manifest root mismatch: recorded {}, entries produce {}",
+                self.root_hash.to_hex(), root.to_hex()
+            );
+        }
+        Ok(())
+    }
+
+    /// The identity this manifest describes.
+    pub fn identity(&self) -> ProjectIdentity {
+        ProjectIdentity {
+            root_hash: Digest::blake3(self.root_hash),
+            file_count: self.files.len(),
+            mutable_files: self.files.iter().filter(|e| e.mutable).map(|e| e.path.clone()).collect(),
+            scheme: self.scheme,
+            preset: self.preset,
+            canonical: self.canonical,
+        }
+    }
+}
+
+/// Serializes `blake3::Hash` as a hex string instead of a byte array.
+pub(crate) mod hex_hash {
+    use blake3::Hash;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(hash: &Hash, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hash.to_hex())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Hash, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Hash::from_hex(s).map_err(serde::de::Error::custom)
+    }
+}
+
+mod octal_mode {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(mode: &u32, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:o}", mode))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        u32::from_str_radix(&s, 8).map_err(serde::de::Error::custom)
+    }
+}
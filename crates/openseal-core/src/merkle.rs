@@ -0,0 +1,291 @@
+use blake3::Hash;
+use serde::{Serialize, Deserialize};
+
+// Domain separation prefixes (RFC 6962 style): a leaf can never be confused with an internal node.
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Version of the leaf encoding used to compute a root hash.
+/// Older roots stay verifiable by recomputing them with the scheme they were sealed under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentityScheme {
+    /// Leaf = path + content.
+    V1,
+    /// Leaf = path + file type + normalized mode + content.
+    V2,
+}
+
+impl IdentityScheme {
+    /// Scheme used for newly computed identities.
+    pub const CURRENT: IdentityScheme = IdentityScheme::V2;
+
+    /// Scheme assumed for identities and proofs serialized without one: the first Merkle leaf encoding.
+    /// Flat roots written before the Merkle tree match no scheme; such bundles must be re-sealed.
+    pub fn legacy() -> Self {
+        IdentityScheme::V1
+    }
+}
+
+/// Type of filesystem entry a leaf represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    File,
+    Symlink,
+}
+
+/// Per-leaf metadata committed by schemes >= V2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeafMeta {
+    pub kind: FileKind,
+    /// Normalized like git: 0o100644, 0o100755 or 0o120000.
+    /// Only the executable bit survives, so umask differences between checkouts don't alter the identity.
+    pub mode: u32,
+}
+
+impl LeafMeta {
+    pub const REGULAR: LeafMeta = LeafMeta { kind: FileKind::File, mode: 0o100644 };
+    pub const EXECUTABLE: LeafMeta = LeafMeta { kind: FileKind::File, mode: 0o100755 };
+    pub const SYMLINK: LeafMeta = LeafMeta { kind: FileKind::Symlink, mode: 0o120000 };
+
+    /// Leaf metadata of a regular file with the given permission bits (as stored in archives).
+    pub fn from_mode(mode: u32) -> Self {
+        if mode & 0o100 != 0 {
+            LeafMeta::EXECUTABLE
+        } else {
+            LeafMeta::REGULAR
+        }
+    }
+
+    /// Derives leaf metadata from filesystem metadata (obtained without following links).
+    pub fn from_metadata(metadata: &std::fs::Metadata) -> Self {
+        if metadata.file_type().is_symlink() {
+            return LeafMeta::SYMLINK;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if metadata.permissions().mode() & 0o100 != 0 {
+                return LeafMeta::EXECUTABLE;
+            }
+        }
+        LeafMeta::REGULAR
+    }
+}
+
+/// Root of a project with no files.
+pub fn empty_root() -> Hash {
+    blake3::hash(b"EMPTY_PROJECT")
+}
+
+/// Computes the Merkle leaf for a file.
+/// V1: Leaf = Hash(0x00 || len(path) || path || file_hash)
+/// V2: Leaf = Hash(0x00 || 0x02 || len(path) || path || kind || mode || file_hash)
+/// The relative path is bound into the leaf so an inclusion proof pins *where* the file lives.
+pub fn leaf_hash(scheme: IdentityScheme, path: &str, meta: &LeafMeta, file_hash: &Hash) -> Hash {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&[LEAF_PREFIX]);
+    if scheme == IdentityScheme::V2 {
+        hasher.update(&[0x02]);
+    }
+    hasher.update(&(path.len() as u64).to_le_bytes());
+    hasher.update(path.as_bytes());
+    if scheme == IdentityScheme::V2 {
+        let kind = match meta.kind {
+            FileKind::File => 0u8,
+            FileKind::Symlink => 1u8,
+        };
+        hasher.update(&[kind]);
+        hasher.update(&meta.mode.to_le_bytes());
+    }
+    hasher.update(file_hash.as_bytes());
+    hasher.finalize()
+}
+
+/// Computes an internal node: Node = Hash(0x01 || left || right)
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&[NODE_PREFIX]);
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    hasher.finalize()
+}
+
+/// Which side of the running hash a sibling sits on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// One step of an inclusion proof: the sibling hash at a given level.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub side: Side,
+    pub hash: Hash,
+}
+
+/// Proof that a single file is part of a sealed `ProjectIdentity`.
+/// `path` and `file_hash` are informational; `verify_inclusion` recomputes the leaf
+/// from the values supplied by the verifier. `meta` is committed by the leaf, so it cannot be altered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    #[serde(default = "IdentityScheme::legacy")]
+    pub scheme: IdentityScheme,
+    pub path: String,
+    pub file_hash: Hash,
+    #[serde(default = "default_leaf_meta")]
+    pub meta: LeafMeta,
+    pub leaf_index: usize,
+    pub leaf_count: usize,
+    pub steps: Vec<ProofStep>,
+}
+
+fn default_leaf_meta() -> LeafMeta {
+    LeafMeta::REGULAR
+}
+
+/// Binary Merkle tree over leaf hashes.
+/// An odd node at the end of a level is promoted unchanged to the next level.
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn from_leaves(leaves: Vec<Hash>) -> Self {
+        let mut levels = vec![leaves];
+        while levels.last().map(|l| l.len() > 1).unwrap_or(false) {
+            let next = levels.last().unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        MerkleTree { levels }
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn root(&self) -> Hash {
+        match self.levels.last().and_then(|l| l.first()) {
+            Some(root) => *root,
+            None => empty_root(),
+        }
+    }
+
+    /// Returns the sibling path for the leaf at `index`, bottom-up.
+    pub fn proof_steps(&self, index: usize) -> Option<Vec<ProofStep>> {
+        if index >= self.leaf_count() {
+            return None;
+        }
+        let mut steps = Vec::new();
+        let mut idx = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = idx ^ 1;
+            if sibling < level.len() {
+                let side = if sibling < idx { Side::Left } else { Side::Right };
+                steps.push(ProofStep { side, hash: level[sibling] });
+            }
+            idx /= 2;
+        }
+        Some(steps)
+    }
+}
+
+/// Verifies that `path` with content hash `file_hash` is included under `root`.
+pub fn verify_inclusion(root: &Hash, path: &str, file_hash: &Hash, proof: &InclusionProof) -> bool {
+    let mut acc = leaf_hash(proof.scheme, path, &proof.meta, file_hash);
+    for step in &proof.steps {
+        acc = match step.side {
+            Side::Left => node_hash(&step.hash, &acc),
+            Side::Right => node_hash(&acc, &step.hash),
+        };
+    }
+    acc == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_leaves(n: usize) -> Vec<(String, Hash)> {
+        (0..n)
+            .map(|i| (format!("src/file_{}.rs", i), blake3::hash(format!("content {}", i).as_bytes())))
+            .collect()
+    }
+
+    fn v2_leaf(path: &str, file_hash: &Hash) -> Hash {
+        leaf_hash(IdentityScheme::V2, path, &LeafMeta::REGULAR, file_hash)
+    }
+
+    fn proof_for(tree: &MerkleTree, leaves: &[(String, Hash)], i: usize) -> InclusionProof {
+        InclusionProof {
+            scheme: IdentityScheme::V2,
+            path: leaves[i].0.clone(),
+            file_hash: leaves[i].1,
+            meta: LeafMeta::REGULAR,
+            leaf_index: i,
+            leaf_count: leaves.len(),
+            steps: tree.proof_steps(i).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_every_leaf_proves_for_various_sizes() {
+        for n in 1..=9 {
+            let leaves = sample_leaves(n);
+            let tree = MerkleTree::from_leaves(leaves.iter().map(|(p, h)| v2_leaf(p, h)).collect());
+            for i in 0..n {
+                let proof = proof_for(&tree, &leaves, i);
+                assert!(verify_inclusion(&tree.root(), &leaves[i].0, &leaves[i].1, &proof), "n={} i={}", n, i);
+            }
+            assert!(tree.proof_steps(n).is_none());
+        }
+    }
+
+    #[test]
+    fn test_proof_rejects_wrong_path_or_content() {
+        let leaves = sample_leaves(5);
+        let tree = MerkleTree::from_leaves(leaves.iter().map(|(p, h)| v2_leaf(p, h)).collect());
+        let proof = proof_for(&tree, &leaves, 2);
+
+        assert!(!verify_inclusion(&tree.root(), "src/other.rs", &leaves[2].1, &proof));
+        assert!(!verify_inclusion(&tree.root(), &leaves[2].0, &blake3::hash(b"tampered"), &proof));
+
+        let mut forged = proof.clone();
+        forged.meta = LeafMeta::EXECUTABLE;
+        assert!(!verify_inclusion(&tree.root(), &leaves[2].0, &leaves[2].1, &forged));
+    }
+
+    #[test]
+    fn test_schemes_produce_distinct_leaves() {
+        let h = blake3::hash(b"content");
+        let v1 = leaf_hash(IdentityScheme::V1, "run.sh", &LeafMeta::REGULAR, &h);
+        assert_eq!(v1, leaf_hash(IdentityScheme::V1, "run.sh", &LeafMeta::EXECUTABLE, &h));
+        assert_ne!(v1, leaf_hash(IdentityScheme::V2, "run.sh", &LeafMeta::REGULAR, &h));
+        assert_ne!(
+            leaf_hash(IdentityScheme::V2, "run.sh", &LeafMeta::REGULAR, &h),
+            leaf_hash(IdentityScheme::V2, "run.sh", &LeafMeta::EXECUTABLE, &h)
+        );
+    }
+
+    #[test]
+    fn test_leaf_and_node_domains_are_separated() {
+        let a = blake3::hash(b"a");
+        let b = blake3::hash(b"b");
+        // A two-leaf tree's root must not be forgeable as a single leaf.
+        let root = MerkleTree::from_leaves(vec![a, b]).root();
+        assert_eq!(root, node_hash(&a, &b));
+        assert_ne!(root, v2_leaf("", &a));
+        assert_eq!(MerkleTree::from_leaves(vec![]).root(), empty_root());
+    }
+}
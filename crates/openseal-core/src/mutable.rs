@@ -0,0 +1,143 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::error::{IdentityError, IdentityResult};
+use crate::policy::POLICY_FILE_NAME;
+
+/// Name of the file declaring mutable paths.
+pub const MUTABLE_FILE_NAME: &str = ".openseal_mutable";
+
+/// Files that define the sealing rules themselves. They can never be mutable, at any depth
+/// (the walker honors ignore files in every directory).
+const RULE_FILES: [&str; 4] = [MUTABLE_FILE_NAME, ".opensealignore", ".ignore", POLICY_FILE_NAME];
+
+fn is_rule_file(rel_path: &str) -> bool {
+    let name = rel_path.rsplit('/').next().unwrap_or(rel_path);
+    RULE_FILES.contains(&name)
+}
+
+/// Patterns from `.openseal_mutable`, matched with gitignore semantics:
+/// - `db` matches a file or directory named exactly `db` at any depth (not `mydb`, not `audit.db`)
+/// - a pattern containing `/` (e.g. `/state.db`, `data/cache.db`) is anchored to the project root
+/// - `**` matches across directories, and `data/` or `data/**` covers everything below `data`
+/// - `!pattern` re-includes a path excluded by an earlier pattern
+pub struct MutablePatterns {
+    matcher: Gitignore,
+    patterns: Vec<String>,
+    matched: BTreeSet<String>,
+}
+
+impl MutablePatterns {
+    /// Loads `.openseal_mutable` from the project root (no file means no mutable paths).
+    pub fn load(root: &Path) -> IdentityResult<Self> {
+        let path = root.join(MUTABLE_FILE_NAME);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(IdentityError::io("read", &path)(e)),
+        };
+        Self::parse(root, &content)
+    }
+
+    /// Parses pattern lines; blank lines and `#` comments are skipped.
+    pub fn parse(root: &Path, content: &str) -> IdentityResult<Self> {
+        let file = Path::new(MUTABLE_FILE_NAME);
+        let mut builder = GitignoreBuilder::new(root);
+        let mut patterns = Vec::new();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            builder.add_line(None, line).map_err(IdentityError::pattern(file))?;
+            patterns.push(line.to_string());
+        }
+        let matcher = builder.build().map_err(IdentityError::pattern(file))?;
+        Ok(MutablePatterns { matcher, patterns, matched: BTreeSet::new() })
+    }
+
+    /// Returns whether the relative path is mutable, recording which pattern decided it.
+    pub fn is_mutable(&mut self, rel_path: &str) -> bool {
+        if is_rule_file(rel_path) {
+            return false;
+        }
+        match self.matcher.matched_path_or_any_parents(rel_path, false) {
+            Match::Ignore(glob) => {
+                self.matched.insert(glob.original().to_string());
+                true
+            }
+            Match::Whitelist(glob) => {
+                self.matched.insert(glob.original().to_string());
+                false
+            }
+            Match::None => false,
+        }
+    }
+
+    /// The pattern deciding a path (`!`-prefixed if it re-includes it), without recording it as used.
+    pub fn deciding_pattern(&self, rel_path: &str) -> Option<String> {
+        if is_rule_file(rel_path) {
+            return None;
+        }
+        match self.matcher.matched_path_or_any_parents(rel_path, false) {
+            Match::Ignore(glob) | Match::Whitelist(glob) => Some(glob.original().to_string()),
+            Match::None => None,
+        }
+    }
+
+    /// Patterns that did not decide any path so far (likely typos or stale entries).
+    pub fn unmatched(&self) -> Vec<String> {
+        self.patterns.iter()
+            .filter(|p| !self.matched.contains(p.as_str()))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(content: &str) -> MutablePatterns {
+        MutablePatterns::parse(Path::new("/project"), content).unwrap()
+    }
+
+    #[test]
+    fn test_bare_name_does_not_match_suffixes() {
+        let mut p = patterns("db\n");
+        assert!(!p.is_mutable("logs/audit.db"));
+        assert!(!p.is_mutable("mydb"));
+        assert!(p.is_mutable("db"));
+        assert!(p.is_mutable("var/db/rows.dat"));
+    }
+
+    #[test]
+    fn test_anchored_directory_and_negation() {
+        let mut p = patterns("/state.db\ndata/**\n!data/schema.sql\n**/*.log\n");
+        assert!(p.is_mutable("state.db"));
+        assert!(!p.is_mutable("nested/state.db"));
+        assert!(p.is_mutable("data/cache/blob.bin"));
+        assert!(!p.is_mutable("data/schema.sql"));
+        assert!(p.is_mutable("logs/app.log"));
+        assert!(!p.is_mutable(MUTABLE_FILE_NAME));
+        assert!(p.unmatched().is_empty());
+    }
+
+    #[test]
+    fn test_rule_files_are_never_mutable() {
+        let mut p = patterns("src/\n*ignore\n");
+        assert!(p.is_mutable("src/main.js"));
+        for path in ["src/.opensealignore", "src/lib/.ignore", ".opensealignore", "src/.openseal_policy.json"] {
+            assert!(!p.is_mutable(path), "{}", path);
+            assert_eq!(p.deciding_pattern(path), None, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_unmatched_patterns_are_reported() {
+        let mut p = patterns("# comment\nuploads/\ncache.sqlite\n");
+        p.is_mutable("uploads/a.png");
+        assert_eq!(p.unmatched(), vec!["cache.sqlite"]);
+    }
+}
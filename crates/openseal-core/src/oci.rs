@@ -0,0 +1,478 @@
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// JSON documents (indexes, manifests, configs) are parsed from memory; larger blobs are only hashed.
+const MAX_DOCUMENT_SIZE: u64 = 4 << 20;
+
+const OCI_INDEX: &str = "application/vnd.oci.image.index.v1+json";
+const DOCKER_MANIFEST_LIST: &str = "application/vnd.docker.distribution.manifest.list.v2+json";
+const REF_NAME_ANNOTATIONS: [&str; 2] = ["io.containerd.image.name", "org.opencontainers.image.ref.name"];
+
+/// Why an image could not be inspected or verified.
+#[derive(Debug, thiserror::Error)]
+pub enum OciError {
+    #[error("Failed to read {path:?}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Neither index.json (OCI layout) nor manifest.json (docker save) found")]
+    NotAnImage,
+    #[error("Missing blob or file: {0}")]
+    Missing(String),
+    #[error("Digest mismatch for {path}: expected {expected}, got {actual}")]
+    DigestMismatch { path: String, expected: String, actual: String },
+    #[error("Size mismatch for {path}: expected {expected} bytes, got {actual}")]
+    SizeMismatch { path: String, expected: u64, actual: u64 },
+    #[error("Unsupported digest algorithm: {0}")]
+    UnsupportedDigest(String),
+    #[error("Invalid {path}: {reason}")]
+    Invalid { path: String, reason: String },
+    #[error("Invalid layer {path}: {source}")]
+    Layer {
+        path: String,
+        #[source]
+        source: crate::IdentityError,
+    },
+    #[error("No image matching {0}")]
+    ImageNotFound(String),
+    #[error("The archive holds {0} images; select one by digest")]
+    AmbiguousImage(usize),
+}
+
+pub type OciResult<T> = std::result::Result<T, OciError>;
+
+/// An OCI content descriptor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Descriptor {
+    #[serde(default)]
+    pub media_type: String,
+    pub digest: String,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Index {
+    manifests: Vec<Descriptor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    config: Descriptor,
+    layers: Vec<Descriptor>,
+}
+
+/// `manifest.json` entry of a (pre-OCI) `docker save` archive.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SaveEntry {
+    config: String,
+    #[serde(default)]
+    repo_tags: Vec<String>,
+    layers: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImageConfig {
+    rootfs: RootFs,
+}
+
+#[derive(Debug, Deserialize)]
+struct RootFs {
+    diff_ids: Vec<String>,
+}
+
+/// Digests of one image, recomputed from its blobs (all referenced blobs were verified).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImageDigests {
+    /// Image name from annotations or `RepoTags`, if recorded.
+    pub reference: Option<String>,
+    /// Registry digest (`image@sha256:...`). Absent for legacy `docker save` archives, which omit the manifest.
+    pub manifest_digest: Option<String>,
+    /// Config digest, shown by Docker as the Image ID.
+    pub config_digest: String,
+    pub layer_digests: Vec<String>,
+    /// Layer files relative to the image root, bottom layer first.
+    #[serde(skip)]
+    pub(crate) layer_paths: Vec<String>,
+}
+
+impl ImageDigests {
+    /// Whether `digest` (registry digest or Image ID) identifies this image.
+    pub fn matches(&self, digest: &str) -> bool {
+        self.manifest_digest.as_deref() == Some(digest) || self.config_digest == digest
+    }
+}
+
+/// Inspects an OCI image layout directory or a `docker save` tarball (optionally gzipped).
+pub fn inspect_image(path: &Path) -> OciResult<Vec<ImageDigests>> {
+    if path.is_dir() {
+        inspect_oci_layout(path)
+    } else {
+        inspect_image_archive(path)
+    }
+}
+
+/// Inspects an OCI image layout directory (`oci-layout`, `index.json`, `blobs/`).
+pub fn inspect_oci_layout(dir: &Path) -> OciResult<Vec<ImageDigests>> {
+    inspect(&DirStore(dir.to_path_buf()))
+}
+
+/// Inspects a `docker save` tarball or a tarred OCI layout, streaming it once.
+pub fn inspect_image_archive(path: &Path) -> OciResult<Vec<ImageDigests>> {
+    let file = fs::File::open(path).map_err(|source| OciError::Io { path: path.to_path_buf(), source })?;
+    inspect(&ArchiveStore::read(file, path)?)
+}
+
+/// Read access to the files of an image, by `/`-separated relative path.
+trait Store {
+    fn exists(&self, path: &str) -> bool;
+    /// sha256 digest (`sha256:<hex>`) and size of a file.
+    fn digest(&self, path: &str) -> OciResult<(String, u64)>;
+    /// Content of a JSON document.
+    fn read(&self, path: &str) -> OciResult<Vec<u8>>;
+}
+
+struct DirStore(PathBuf);
+
+impl Store for DirStore {
+    fn exists(&self, path: &str) -> bool {
+        self.0.join(path).is_file()
+    }
+
+    fn digest(&self, path: &str) -> OciResult<(String, u64)> {
+        let full = self.0.join(path);
+        let file = fs::File::open(&full).map_err(|source| match source.kind() {
+            std::io::ErrorKind::NotFound => OciError::Missing(path.to_string()),
+            _ => OciError::Io { path: full.clone(), source },
+        })?;
+        sha256_stream(file).map_err(|source| OciError::Io { path: full, source })
+    }
+
+    fn read(&self, path: &str) -> OciResult<Vec<u8>> {
+        let full = self.0.join(path);
+        let size = fs::metadata(&full).map_err(|_| OciError::Missing(path.to_string()))?.len();
+        if size > MAX_DOCUMENT_SIZE {
+            return Err(invalid(path, "document is too large"));
+        }
+        fs::read(&full).map_err(|source| OciError::Io { path: full, source })
+    }
+}
+
+struct ArchivedFile {
+    digest: String,
+    size: u64,
+    content: Option<Vec<u8>>,
+}
+
+/// Every regular file of the archive, hashed in a single streaming pass.
+struct ArchiveStore(HashMap<String, ArchivedFile>);
+
+impl ArchiveStore {
+    fn read<R: Read>(reader: R, archive_path: &Path) -> OciResult<Self> {
+        let io_error = |source| OciError::Io { path: archive_path.to_path_buf(), source };
+        let mut reader = BufReader::new(reader);
+        let gzipped = reader.fill_buf().map_err(io_error)?.starts_with(&[0x1f, 0x8b]);
+        let reader: Box<dyn Read> = if gzipped { Box::new(GzDecoder::new(reader)) } else { Box::new(reader) };
+
+        let mut files = HashMap::new();
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries().map_err(io_error)? {
+            let mut entry = entry.map_err(io_error)?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let path = entry.path().map_err(io_error)?.to_string_lossy().trim_start_matches("./").to_string();
+            let keep = entry.size() <= MAX_DOCUMENT_SIZE;
+            let mut hasher = Sha256::new();
+            let mut content = keep.then(Vec::new);
+            let mut size = 0u64;
+            let mut buffer = [0; 65536];
+            loop {
+                let count = entry.read(&mut buffer).map_err(io_error)?;
+                if count == 0 { break; }
+                hasher.update(&buffer[..count]);
+                if let Some(content) = content.as_mut() {
+                    content.extend_from_slice(&buffer[..count]);
+                }
+                size += count as u64;
+            }
+            let digest = format!("sha256:{}", hex::encode(hasher.finalize()));
+            files.insert(path, ArchivedFile { digest, size, content });
+        }
+        Ok(ArchiveStore(files))
+    }
+
+    fn get(&self, path: &str) -> OciResult<&ArchivedFile> {
+        self.0.get(path).ok_or_else(|| OciError::Missing(path.to_string()))
+    }
+}
+
+impl Store for ArchiveStore {
+    fn exists(&self, path: &str) -> bool {
+        self.0.contains_key(path)
+    }
+
+    fn digest(&self, path: &str) -> OciResult<(String, u64)> {
+        let file = self.get(path)?;
+        Ok((file.digest.clone(), file.size))
+    }
+
+    fn read(&self, path: &str) -> OciResult<Vec<u8>> {
+        self.get(path)?.content.clone().ok_or_else(|| invalid(path, "document is too large"))
+    }
+}
+
+fn inspect(store: &dyn Store) -> OciResult<Vec<ImageDigests>> {
+    if store.exists("index.json") {
+        let index: Index = parse(store, "index.json")?;
+        let mut images = Vec::new();
+        for descriptor in &index.manifests {
+            collect_manifest(store, descriptor, None, &mut images)?;
+        }
+        Ok(images)
+    } else if store.exists("manifest.json") {
+        inspect_docker_save(store)
+    } else {
+        Err(OciError::NotAnImage)
+    }
+}
+
+/// Verifies a manifest (or nested index) and all blobs it references.
+fn collect_manifest(
+    store: &dyn Store,
+    descriptor: &Descriptor,
+    parent_reference: Option<&str>,
+    images: &mut Vec<ImageDigests>,
+) -> OciResult<()> {
+    let path = blob_path(&descriptor.digest)?;
+    verify_blob(store, descriptor)?;
+    let reference = REF_NAME_ANNOTATIONS.iter()
+        .find_map(|key| descriptor.annotations.get(*key).map(String::as_str))
+        .or(parent_reference);
+
+    let document: serde_json::Value = parse(store, &path)?;
+    let is_index = descriptor.media_type == OCI_INDEX
+        || descriptor.media_type == DOCKER_MANIFEST_LIST
+        || document.get("manifests").is_some();
+    if is_index {
+        let index: Index = serde_json::from_value(document).map_err(|e| invalid(&path, &e.to_string()))?;
+        for child in &index.manifests {
+            collect_manifest(store, child, reference, images)?;
+        }
+        return Ok(());
+    }
+
+    let manifest: Manifest = serde_json::from_value(document).map_err(|e| invalid(&path, &e.to_string()))?;
+    verify_blob(store, &manifest.config)?;
+    let mut layer_paths = Vec::with_capacity(manifest.layers.len());
+    for layer in &manifest.layers {
+        verify_blob(store, layer)?;
+        layer_paths.push(blob_path(&layer.digest)?);
+    }
+    images.push(ImageDigests {
+        reference: reference.map(str::to_string),
+        manifest_digest: Some(descriptor.digest.clone()),
+        config_digest: manifest.config.digest,
+        layer_digests: manifest.layers.into_iter().map(|l| l.digest).collect(),
+        layer_paths,
+    });
+    Ok(())
+}
+
+/// Legacy `docker save` layout: configs are named by their digest, layers are checked against `rootfs.diff_ids`.
+fn inspect_docker_save(store: &dyn Store) -> OciResult<Vec<ImageDigests>> {
+    let entries: Vec<SaveEntry> = parse(store, "manifest.json")?;
+    let mut images = Vec::new();
+    for entry in entries {
+        let (config_digest, _) = store.digest(&entry.config)?;
+        let named = Path::new(&entry.config).file_name()
+            .map(|name| name.to_string_lossy().trim_end_matches(".json").to_string())
+            .unwrap_or_default();
+        if format!("sha256:{}", named) != config_digest {
+            return Err(OciError::DigestMismatch {
+                path: entry.config.clone(),
+                expected: format!("sha256:{}", named),
+                actual: config_digest,
+            });
+        }
+
+        let config: ImageConfig = parse(store, &entry.config)?;
+        if config.rootfs.diff_ids.len() != entry.layers.len() {
+            return Err(invalid(&entry.config, "layer count does not match rootfs.diff_ids"));
+        }
+        for (layer, expected) in entry.layers.iter().zip(&config.rootfs.diff_ids) {
+            let (actual, _) = store.digest(layer)?;
+            if &actual != expected {
+                return Err(OciError::DigestMismatch { path: layer.clone(), expected: expected.clone(), actual });
+            }
+        }
+        images.push(ImageDigests {
+            reference: entry.repo_tags.into_iter().next(),
+            manifest_digest: None,
+            config_digest,
+            layer_digests: config.rootfs.diff_ids,
+            layer_paths: entry.layers,
+        });
+    }
+    Ok(images)
+}
+
+/// Streams the files at `paths` (relative to the image root) through `visit`, in storage order.
+/// An archive is read in a single pass, so the files are not visited in any particular order.
+pub(crate) fn for_each_file(
+    image_path: &Path,
+    paths: &HashSet<&str>,
+    mut visit: impl FnMut(&str, &mut dyn Read) -> OciResult<()>,
+) -> OciResult<()> {
+    let io_error = |source| OciError::Io { path: image_path.to_path_buf(), source };
+    if image_path.is_dir() {
+        for path in paths {
+            let full = image_path.join(path);
+            let mut file = fs::File::open(&full).map_err(|source| OciError::Io { path: full, source })?;
+            visit(path, &mut file)?;
+        }
+        return Ok(());
+    }
+
+    let file = fs::File::open(image_path).map_err(io_error)?;
+    let mut reader = BufReader::new(file);
+    let gzipped = reader.fill_buf().map_err(io_error)?.starts_with(&[0x1f, 0x8b]);
+    let reader: Box<dyn Read> = if gzipped { Box::new(GzDecoder::new(reader)) } else { Box::new(reader) };
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries().map_err(io_error)? {
+        let mut entry = entry.map_err(io_error)?;
+        let path = entry.path().map_err(io_error)?.to_string_lossy().trim_start_matches("./").to_string();
+        if entry.header().entry_type().is_file() && paths.contains(path.as_str()) {
+            visit(&path, &mut entry)?;
+        }
+    }
+    Ok(())
+}
+
+fn verify_blob(store: &dyn Store, descriptor: &Descriptor) -> OciResult<()> {
+    let path = blob_path(&descriptor.digest)?;
+    let (actual, size) = store.digest(&path)?;
+    if actual != descriptor.digest {
+        return Err(OciError::DigestMismatch { path, expected: descriptor.digest.clone(), actual });
+    }
+    if size != descriptor.size {
+        return Err(OciError::SizeMismatch { path, expected: descriptor.size, actual: size });
+    }
+    Ok(())
+}
+
+fn blob_path(digest: &str) -> OciResult<String> {
+    match digest.split_once(':') {
+        Some(("sha256", hex)) if hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            Ok(format!("blobs/sha256/{}", hex))
+        }
+        _ => Err(OciError::UnsupportedDigest(digest.to_string())),
+    }
+}
+
+fn parse<T: serde::de::DeserializeOwned>(store: &dyn Store, path: &str) -> OciResult<T> {
+    serde_json::from_slice(&store.read(path)?).map_err(|e| invalid(path, &e.to_string()))
+}
+
+fn sha256_stream(mut reader: impl Read) -> std::io::Result<(String, u64)> {
+    let mut hasher = Sha256::new();
+    let size = std::io::copy(&mut reader, &mut hasher)?;
+    Ok((format!("sha256:{}", hex::encode(hasher.finalize())), size))
+}
+
+fn invalid(path: &str, reason: &str) -> OciError {
+    OciError::Invalid { path: path.to_string(), reason: reason.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn sha256(data: &[u8]) -> String {
+        format!("sha256:{}", hex::encode(Sha256::digest(data)))
+    }
+
+    fn write_blob(dir: &Path, data: &[u8]) -> anyhow::Result<Descriptor> {
+        let digest = sha256(data);
+        fs::create_dir_all(dir.join("blobs/sha256"))?;
+        fs::write(dir.join(blob_path(&digest)?), data)?;
+        Ok(Descriptor { media_type: String::new(), digest, size: data.len() as u64, annotations: BTreeMap::new() })
+    }
+
+    /// Writes a single-image OCI layout and returns the manifest descriptor.
+    fn oci_layout(dir: &Path) -> anyhow::Result<Descriptor> {
+        let layer = write_blob(dir, b"layer bytes")?;
+        let config = write_blob(dir, br#"{"rootfs":{"type":"layers","diff_ids":[]}}"#)?;
+        let manifest = serde_json::json!({ "schemaVersion": 2, "config": config, "layers": [layer] });
+        let mut descriptor = write_blob(dir, manifest.to_string().as_bytes())?;
+        descriptor.media_type = "application/vnd.oci.image.manifest.v1+json".into();
+        descriptor.annotations.insert("org.opencontainers.image.ref.name".into(), "api:1.0".into());
+        fs::write(dir.join("index.json"), serde_json::json!({ "schemaVersion": 2, "manifests": [descriptor] }).to_string())?;
+        fs::write(dir.join("oci-layout"), r#"{"imageLayoutVersion":"1.0.0"}"#)?;
+        Ok(descriptor)
+    }
+
+    #[test]
+    fn test_oci_layout_digests_directory_and_tarball() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let manifest = oci_layout(dir.path())?;
+
+        let images = inspect_oci_layout(dir.path())?;
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].manifest_digest.as_deref(), Some(manifest.digest.as_str()));
+        assert_eq!(images[0].reference.as_deref(), Some("api:1.0"));
+        assert_eq!(images[0].config_digest, sha256(br#"{"rootfs":{"type":"layers","diff_ids":[]}}"#));
+        assert!(images[0].matches(&manifest.digest));
+
+        let mut builder = tar::Builder::new(Vec::new());
+        builder.append_dir_all(".", dir.path())?;
+        let tar_path = dir.path().join("image.tar");
+        fs::write(&tar_path, builder.into_inner()?)?;
+        assert_eq!(inspect_image(&tar_path)?, images);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tampered_blob_is_rejected() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        oci_layout(dir.path())?;
+        fs::write(dir.path().join(blob_path(&sha256(b"layer bytes"))?), b"LAYER bytes")?;
+
+        let err = inspect_oci_layout(dir.path()).unwrap_err();
+        assert!(matches!(err, OciError::DigestMismatch { .. }), "{}", err);
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_docker_save_checks_diff_ids() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let layer = b"uncompressed layer tar";
+        let config = serde_json::json!({ "rootfs": { "type": "layers", "diff_ids": [sha256(layer)] } }).to_string();
+        let config_name = format!("{}.json", &sha256(config.as_bytes())[7..]);
+        fs::write(dir.path().join(&config_name), &config)?;
+        fs::create_dir_all(dir.path().join("abc"))?;
+        fs::write(dir.path().join("abc/layer.tar"), layer)?;
+        fs::write(dir.path().join("manifest.json"), serde_json::json!([{
+            "Config": config_name, "RepoTags": ["api:latest"], "Layers": ["abc/layer.tar"]
+        }]).to_string())?;
+
+        let images = inspect_image(dir.path())?;
+        assert_eq!(images[0].manifest_digest, None);
+        assert!(images[0].matches(&sha256(config.as_bytes())));
+
+        fs::write(dir.path().join("abc/layer.tar"), b"swapped layer")?;
+        assert!(matches!(inspect_image(dir.path()), Err(OciError::DigestMismatch { .. })));
+        Ok(())
+    }
+}
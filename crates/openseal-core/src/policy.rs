@@ -0,0 +1,266 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use crate::error::{IdentityError, IdentityResult};
+
+/// Per-project policy file. It is a regular project file, so it is sealed like any other code.
+pub const POLICY_FILE_NAME: &str = ".openseal_policy.json";
+
+/// Paths that can never be mutable unless explicitly allowed by the project policy.
+/// Matched with gitignore semantics (a bare `*.rs` matches at any depth).
+pub const DEFAULT_DENY: &[&str] = &[
+    // Source code
+    "*.rs", "*.js", "*.mjs", "*.cjs", "*.jsx", "*.ts", "*.tsx", "*.py", "*.pyc", "*.go",
+    "*.c", "*.cc", "*.cpp", "*.h", "*.hpp", "*.java", "*.kt", "*.class", "*.jar",
+    "*.php", "*.rb", "*.lua", "*.pl", "*.pm", "*.cs", "*.swift",
+    // Scripts and binaries
+    "*.sh", "*.bash", "*.zsh", "*.ps1", "*.bat", "*.cmd", "*.exe", "*.dll", "*.so", "*.dylib", "*.wasm",
+    // Configuration: named files only, so JSON/TOML/YAML data can be mutable
+    "package.json", "package-lock.json", "tsconfig.json", "jsconfig.json", "composer.json", "deno.json",
+    "openseal.json", "Cargo.toml", "Cargo.lock", "pyproject.toml", "Pipfile", "requirements.txt",
+    "go.mod", "go.sum", "pom.xml", "*.gradle", "*.gradle.kts", "yarn.lock", "pnpm-lock.yaml",
+    "docker-compose.yml", "docker-compose.yaml", "compose.yml", "compose.yaml",
+    "*.ini", "*.cfg", "*.conf", ".env", ".env.*", "Dockerfile", "Makefile",
+];
+
+/// Serialized form of `.openseal_policy.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyConfig {
+    /// Globs that may be mutable even if a deny rule matches (e.g. `data/**/*.json`).
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Additional globs that must never be mutable.
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Reject mutable files whose content looks executable (shebang, ELF, Mach-O, PE, WASM).
+    /// Allow globs do not override this check.
+    #[serde(default = "default_true")]
+    pub sniff_content: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for PolicyConfig {
+    fn default() -> Self {
+        PolicyConfig { allow: Vec::new(), deny: Vec::new(), sniff_content: true }
+    }
+}
+
+impl PolicyConfig {
+    /// Parses the content of `.openseal_policy.json`.
+    pub fn parse(content: &str) -> IdentityResult<Self> {
+        serde_json::from_str(content).map_err(|source| IdentityError::InvalidPolicyFile { source })
+    }
+}
+
+/// Number of leading bytes needed by `ExecutableKind::sniff`.
+pub const SNIFF_LEN: usize = 4;
+
+/// Kind of executable content detected by sniffing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExecutableKind {
+    Shebang,
+    Elf,
+    MachO,
+    Pe,
+    Wasm,
+}
+
+impl fmt::Display for ExecutableKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ExecutableKind::Shebang => "shebang script",
+            ExecutableKind::Elf => "ELF binary",
+            ExecutableKind::MachO => "Mach-O binary",
+            ExecutableKind::Pe => "PE executable",
+            ExecutableKind::Wasm => "WebAssembly module",
+        };
+        f.write_str(name)
+    }
+}
+
+impl ExecutableKind {
+    /// Detects executable content from the first bytes of a file.
+    pub fn sniff(head: &[u8]) -> Option<Self> {
+        const MACHO_MAGIC: [[u8; 4]; 5] = [
+            [0xFE, 0xED, 0xFA, 0xCE], [0xFE, 0xED, 0xFA, 0xCF],
+            [0xCE, 0xFA, 0xED, 0xFE], [0xCF, 0xFA, 0xED, 0xFE],
+            [0xCA, 0xFE, 0xBA, 0xBE], // Fat binary (also Java class files)
+        ];
+        if head.starts_with(b"#!") {
+            Some(ExecutableKind::Shebang)
+        } else if head.starts_with(b"\x7fELF") {
+            Some(ExecutableKind::Elf)
+        } else if head.starts_with(b"\0asm") {
+            Some(ExecutableKind::Wasm)
+        } else if head.starts_with(b"MZ") {
+            Some(ExecutableKind::Pe)
+        } else if MACHO_MAGIC.iter().any(|m| head.starts_with(m)) {
+            Some(ExecutableKind::MachO)
+        } else {
+            None
+        }
+    }
+}
+
+/// Why a path was refused as mutable.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PolicyViolation {
+    #[error("SECURITY VIOLATION: '{path}' cannot be mutable. It matches deny rule '{rule}'.")]
+    DeniedPattern { path: String, rule: String },
+    #[error("SECURITY VIOLATION: '{path}' cannot be mutable. Its content is executable ({kind}).")]
+    ExecutableContent { path: String, kind: ExecutableKind },
+    #[error("SECURITY VIOLATION: '{path}' cannot be sniffed for executable content: {reason}")]
+    Unreadable { path: String, reason: String },
+}
+
+impl PolicyViolation {
+    pub fn path(&self) -> &str {
+        match self {
+            PolicyViolation::DeniedPattern { path, .. }
+            | PolicyViolation::ExecutableContent { path, .. }
+            | PolicyViolation::Unreadable { path, .. } => path,
+        }
+    }
+}
+
+/// Security policy deciding which files may be declared mutable.
+pub struct MutablePolicy {
+    deny: Gitignore,
+    allow: Gitignore,
+    sniff_content: bool,
+}
+
+impl MutablePolicy {
+    /// Loads `.openseal_policy.json` from the project root, falling back to the default policy.
+    pub fn load(root: &Path) -> IdentityResult<Self> {
+        let path = root.join(POLICY_FILE_NAME);
+        let config = match fs::read_to_string(&path) {
+            Ok(content) => PolicyConfig::parse(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => PolicyConfig::default(),
+            Err(e) => return Err(IdentityError::io("read", &path)(e)),
+        };
+        Self::from_config(root, &config)
+    }
+
+    pub fn from_config(root: &Path, config: &PolicyConfig) -> IdentityResult<Self> {
+        let file = Path::new(POLICY_FILE_NAME);
+        let build = |globs: &mut dyn Iterator<Item = &str>| -> IdentityResult<Gitignore> {
+            let mut builder = GitignoreBuilder::new(root);
+            for glob in globs {
+                builder.add_line(None, glob).map_err(IdentityError::pattern(file))?;
+            }
+            builder.build().map_err(IdentityError::pattern(file))
+        };
+        let deny = build(&mut DEFAULT_DENY.iter().copied().chain(config.deny.iter().map(String::as_str)))?;
+        let allow = build(&mut config.allow.iter().map(String::as_str))?;
+        Ok(MutablePolicy { deny, allow, sniff_content: config.sniff_content })
+    }
+
+    /// Checks a mutable candidate by path only.
+    pub fn check_path(&self, rel_path: &str) -> std::result::Result<(), PolicyViolation> {
+        if let Match::Ignore(glob) = self.deny.matched_path_or_any_parents(rel_path, false) {
+            if !self.allow.matched_path_or_any_parents(rel_path, false).is_ignore() {
+                return Err(PolicyViolation::DeniedPattern {
+                    path: rel_path.to_string(),
+                    rule: glob.original().to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks a mutable candidate by path and (if enabled) by its current content.
+    pub fn check(&self, rel_path: &str, abs_path: &Path) -> std::result::Result<(), PolicyViolation> {
+        self.check_path(rel_path)?;
+        if !self.sniff_content {
+            return Ok(());
+        }
+        let mut head = [0u8; SNIFF_LEN];
+        let read = fs::File::open(abs_path)
+            .and_then(|f| f.take(head.len() as u64).read(&mut head))
+            .map_err(|e| PolicyViolation::Unreadable { path: rel_path.to_string(), reason: e.to_string() })?;
+        self.check_head(rel_path, &head[..read])
+    }
+
+    /// Checks a mutable candidate by path and the first `SNIFF_LEN` bytes of its content.
+    pub fn check_head(&self, rel_path: &str, head: &[u8]) -> std::result::Result<(), PolicyViolation> {
+        self.check_path(rel_path)?;
+        if !self.sniff_content {
+            return Ok(());
+        }
+        match ExecutableKind::sniff(head) {
+            Some(kind) => Err(PolicyViolation::ExecutableContent { path: rel_path.to_string(), kind }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    fn policy(config: PolicyConfig) -> MutablePolicy {
+        MutablePolicy::from_config(Path::new("/project"), &config).unwrap()
+    }
+
+    #[test]
+    fn test_default_policy_denies_code_and_allows_data() {
+        let p = policy(PolicyConfig::default());
+        for path in ["src/index.php", "lib/app.rb", "plugins/init.lua", "vendor/x.jar", "package.json", "web/tsconfig.json"] {
+            assert!(matches!(p.check_path(path), Err(PolicyViolation::DeniedPattern { .. })), "{}", path);
+        }
+        for path in ["data/state.db", "data/prices.json", "state.toml", "feeds.yaml"] {
+            assert!(p.check_path(path).is_ok(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_allow_and_deny_globs() {
+        let p = policy(PolicyConfig {
+            allow: vec!["data/**/*.json".into()],
+            deny: vec!["*.db".into()],
+            sniff_content: true,
+        });
+        assert!(p.check_path("data/users/list.json").is_ok());
+        assert!(p.check_path("composer.json").is_err());
+        assert_eq!(
+            p.check_path("cache.db"),
+            Err(PolicyViolation::DeniedPattern { path: "cache.db".into(), rule: "*.db".into() })
+        );
+    }
+
+    #[test]
+    fn test_content_sniffing_catches_extensionless_executables() -> Result<()> {
+        let dir = tempdir()?;
+        let cases: [(&str, &[u8], Option<ExecutableKind>); 4] = [
+            ("run", b"#!/bin/sh\necho pwned", Some(ExecutableKind::Shebang)),
+            ("blob", b"\x7fELF\x02\x01", Some(ExecutableKind::Elf)),
+            ("module", b"\0asm\x01\0\0\0", Some(ExecutableKind::Wasm)),
+            ("notes", b"plain text", None),
+        ];
+        let p = policy(PolicyConfig::default());
+        for (name, content, expected) in cases {
+            fs::write(dir.path().join(name), content)?;
+            let result = p.check(name, &dir.path().join(name));
+            match expected {
+                Some(kind) => assert_eq!(result, Err(PolicyViolation::ExecutableContent { path: name.into(), kind })),
+                None => assert!(result.is_ok()),
+            }
+        }
+
+        let lenient = policy(PolicyConfig { sniff_content: false, ..PolicyConfig::default() });
+        assert!(lenient.check("run", &dir.path().join("run")).is_ok());
+        Ok(())
+    }
+}
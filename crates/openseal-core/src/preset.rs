@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Component, Path};
+
+/// Directories excluded under every preset.
+const COMMON_EXCLUDES: &[&str] = &[".git"];
+
+/// Standard Exclusion Rules for a project type (see AGNOSTICISM.md).
+/// Presets only drop dependency and build by-product directories, never sources: generic names
+/// (`target`, `build`) are only excluded in the project root, so a nested `src/build/` stays sealed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LanguagePreset {
+    /// No marker file found: only the common exclusions apply.
+    Generic,
+    Node,
+    Python,
+    Rust,
+    Go,
+    Java,
+}
+
+impl LanguagePreset {
+    /// Detection order; the first preset with a marker file in the project root wins.
+    const DETECTION_ORDER: [LanguagePreset; 5] = [
+        LanguagePreset::Node,
+        LanguagePreset::Python,
+        LanguagePreset::Rust,
+        LanguagePreset::Go,
+        LanguagePreset::Java,
+    ];
+
+    /// Detects the project type from marker files in the project root.
+    pub fn detect(root: &Path) -> Self {
+        Self::detect_with(|marker| root.join(marker).exists())
+    }
+
+    /// Detects the project type given a check for root-level files (e.g. inside an archive).
+    pub fn detect_with(exists: impl Fn(&str) -> bool) -> Self {
+        Self::DETECTION_ORDER.into_iter()
+            .find(|preset| preset.marker_files().iter().any(|m| exists(m)))
+            .unwrap_or(LanguagePreset::Generic)
+    }
+
+    /// Files whose presence in the project root selects this preset.
+    pub fn marker_files(&self) -> &'static [&'static str] {
+        match self {
+            LanguagePreset::Generic => &[],
+            LanguagePreset::Node => &["package.json"],
+            LanguagePreset::Python => &["requirements.txt", "pyproject.toml", "setup.py"],
+            LanguagePreset::Rust => &["Cargo.toml"],
+            LanguagePreset::Go => &["go.mod"],
+            LanguagePreset::Java => &["pom.xml", "build.gradle", "build.gradle.kts"],
+        }
+    }
+
+    /// Excluded directories, including the common exclusions: a name matches at any depth,
+    /// `/name` only in the project root.
+    pub fn exclude_dirs(&self) -> Vec<&'static str> {
+        let specific: &[&str] = match self {
+            LanguagePreset::Generic => &[],
+            LanguagePreset::Node => &["node_modules", ".npm", ".next", ".turbo"],
+            LanguagePreset::Python => &["/venv", ".venv", "__pycache__", ".pytest_cache", ".mypy_cache", ".tox"],
+            LanguagePreset::Rust => &["/target"],
+            // `vendor/` is sealed: vendored modules are what `go build -mod=vendor` compiles
+            LanguagePreset::Go => &[],
+            LanguagePreset::Java => &["/target", "/build", "/.gradle"],
+        };
+        COMMON_EXCLUDES.iter().chain(specific).copied().collect()
+    }
+}
+
+/// Excluded directories with the rule that excludes each, as in `LanguagePreset::exclude_dirs`:
+/// `name` matches at any depth, `/name` only in the project root.
+#[derive(Debug, Clone, Default)]
+pub struct ExclusionRules {
+    names: HashMap<String, String>,
+    root_names: HashMap<String, String>,
+}
+
+impl ExclusionRules {
+    pub fn insert(&mut self, pattern: &str, rule: String) {
+        match pattern.strip_prefix('/') {
+            Some(name) => self.root_names.insert(name.to_string(), rule),
+            None => self.names.insert(pattern.to_string(), rule),
+        };
+    }
+
+    /// Rule excluding the directory `name` at `depth` (1 for the project root's entries).
+    pub fn rule(&self, name: &str, depth: usize) -> Option<&String> {
+        self.names.get(name).or_else(|| if depth == 1 { self.root_names.get(name) } else { None })
+    }
+
+    /// Whether any component of `relative` (a path below the project root) is excluded.
+    pub fn excludes(&self, relative: &Path) -> bool {
+        relative.components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .enumerate()
+            .any(|(i, component)| {
+                component.as_os_str().to_str().is_some_and(|name| self.rule(name, i + 1).is_some())
+            })
+    }
+}
+
+impl fmt::Display for LanguagePreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LanguagePreset::Generic => "generic",
+            LanguagePreset::Node => "node",
+            LanguagePreset::Python => "python",
+            LanguagePreset::Rust => "rust",
+            LanguagePreset::Go => "go",
+            LanguagePreset::Java => "java",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detection_follows_marker_files() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        assert_eq!(LanguagePreset::detect(dir.path()), LanguagePreset::Generic);
+
+        std::fs::write(dir.path().join("go.mod"), "module x")?;
+        assert_eq!(LanguagePreset::detect(dir.path()), LanguagePreset::Go);
+
+        // Node wins over other markers, as in the runtime's dependency detection
+        std::fs::write(dir.path().join("package.json"), "{}")?;
+        assert_eq!(LanguagePreset::detect(dir.path()), LanguagePreset::Node);
+        assert!(LanguagePreset::Node.exclude_dirs().contains(&".git"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_generic_names_are_excluded_in_the_root_only() {
+        let mut rules = ExclusionRules::default();
+        for pattern in LanguagePreset::Java.exclude_dirs() {
+            rules.insert(pattern, pattern.to_string());
+        }
+        assert!(rules.excludes(Path::new("build/classes/App.class")));
+        assert!(rules.excludes(Path::new("module/.git/HEAD")));
+        assert!(!rules.excludes(Path::new("src/main/build/Builder.java")));
+        assert_eq!(rules.rule("target", 1).map(String::as_str), Some("/target"));
+        assert_eq!(rules.rule("target", 2), None);
+    }
+}
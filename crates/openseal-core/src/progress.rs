@@ -0,0 +1,106 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::error::{IdentityError, IdentityResult};
+
+/// Where a scan is, reported to the `ProgressCallback` of `IdentityOptions`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanProgress {
+    /// Files and symlinks found by the walker so far.
+    pub files_discovered: u64,
+    /// Set once the walk is over: `files_discovered` is then the final count.
+    pub discovery_complete: bool,
+    /// Leaves computed so far (cached, mutable and symlink entries included).
+    pub files_hashed: u64,
+    /// Content bytes read and hashed so far (cached files are not read).
+    pub bytes_hashed: u64,
+}
+
+/// Receives `ScanProgress` snapshots. It is called from the hashing threads,
+/// once per discovered and per hashed file, so it should return quickly.
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(ScanProgress) + Send + Sync>);
+
+impl ProgressCallback {
+    pub fn new(callback: impl Fn(ScanProgress) + Send + Sync + 'static) -> Self {
+        ProgressCallback(Arc::new(callback))
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// Aborts a running scan from another thread or task, which then fails with `IdentityError::Cancelled`.
+/// Checked before each file, so a file already being hashed is finished first.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Counters of one scan, shared by the walker and the hashing threads.
+#[derive(Debug, Default)]
+pub(crate) struct ScanMonitor {
+    callback: Option<ProgressCallback>,
+    cancel: CancellationToken,
+    files_discovered: AtomicU64,
+    discovery_complete: AtomicBool,
+    files_hashed: AtomicU64,
+    bytes_hashed: AtomicU64,
+}
+
+impl ScanMonitor {
+    pub(crate) fn new(callback: Option<ProgressCallback>, cancel: CancellationToken) -> Self {
+        ScanMonitor { callback, cancel, ..Default::default() }
+    }
+
+    pub(crate) fn check(&self) -> IdentityResult<()> {
+        if self.cancel.is_cancelled() {
+            return Err(IdentityError::Cancelled);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn file_discovered(&self) {
+        self.files_discovered.fetch_add(1, Ordering::Relaxed);
+        self.emit();
+    }
+
+    pub(crate) fn discovery_complete(&self) {
+        self.discovery_complete.store(true, Ordering::Relaxed);
+        self.emit();
+    }
+
+    /// A leaf is done; `bytes` is how much content was read for it.
+    pub(crate) fn file_hashed(&self, bytes: u64) {
+        self.bytes_hashed.fetch_add(bytes, Ordering::Relaxed);
+        self.files_hashed.fetch_add(1, Ordering::Relaxed);
+        self.emit();
+    }
+
+    fn emit(&self) {
+        if let Some(callback) = &self.callback {
+            (callback.0)(ScanProgress {
+                files_discovered: self.files_discovered.load(Ordering::Relaxed),
+                discovery_complete: self.discovery_complete.load(Ordering::Relaxed),
+                files_hashed: self.files_hashed.load(Ordering::Relaxed),
+                bytes_hashed: self.bytes_hashed.load(Ordering::Relaxed),
+            });
+        }
+    }
+}
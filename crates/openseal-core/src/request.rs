@@ -0,0 +1,145 @@
+use blake3::Hash;
+
+/// The HTTP request a seal answers, as the runtime received it.
+/// Its `digest` is signed into V2 seals, so a result can't be passed off as the answer to another endpoint or input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestDescription {
+    /// Upper-cased method.
+    pub method: String,
+    /// Normalized path: no empty or dot segments, unreserved characters decoded, escapes upper-cased.
+    pub path: String,
+    /// Normalized query: parameters escaped like the path and sorted by key, empty ones dropped.
+    /// Repeated keys keep their order, as applications may read them as a list.
+    pub query: String,
+    pub body_hash: Hash,
+}
+
+impl RequestDescription {
+    /// `target` is the path with an optional `?query`, as sent to the runtime.
+    pub fn new(method: &str, target: &str, body: &[u8]) -> Self {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        RequestDescription::from_parts(method, path, query, blake3::hash(body))
+    }
+
+    pub fn from_parts(method: &str, path: &str, query: &str, body_hash: Hash) -> Self {
+        RequestDescription {
+            method: method.to_ascii_uppercase(),
+            path: normalize_path(path),
+            query: normalize_query(query),
+            body_hash,
+        }
+    }
+
+    /// Normalized path with the query, if any: the target the runtime forwards to the application.
+    pub fn target(&self) -> String {
+        if self.query.is_empty() {
+            self.path.clone()
+        } else {
+            format!("{}?{}", self.path, self.query)
+        }
+    }
+
+    /// Canonical form: method, path, query and body hash, one per line.
+    pub fn canonical(&self) -> String {
+        format!("{}\n{}\n{}\n{}", self.method, self.path, self.query, self.body_hash.to_hex())
+    }
+
+    pub fn digest(&self) -> Hash {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(b"OPENSEAL_REQUEST\n");
+        hasher.update(self.canonical().as_bytes());
+        hasher.finalize()
+    }
+}
+
+fn normalize_path(path: &str) -> String {
+    let mut segments: Vec<String> = Vec::new();
+    for segment in path.split('/') {
+        // Escapes first, so `%2E%2E` is resolved like `..` (RFC 3986, 6.2.2)
+        match normalize_escapes(segment).as_str() {
+            "" | "." => {}
+            ".." => { segments.pop(); }
+            other => segments.push(other.to_string()),
+        }
+    }
+    let trailing_slash = !segments.is_empty() && path.ends_with('/');
+    format!("/{}{}", segments.join("/"), if trailing_slash { "/" } else { "" })
+}
+
+fn normalize_query(query: &str) -> String {
+    let mut params: Vec<String> = query.split('&')
+        .filter(|param| !param.is_empty())
+        .map(normalize_escapes)
+        .collect();
+    // Stable, so `a=1&a=2` and `a=2&a=1` stay different requests
+    params.sort_by(|a, b| query_key(a).cmp(query_key(b)));
+    params.join("&")
+}
+
+fn query_key(param: &str) -> &str {
+    param.split_once('=').map_or(param, |(key, _)| key)
+}
+
+/// Decodes escaped unreserved characters (`%41` -> `A`) and upper-cases the hex of the other escapes.
+fn normalize_escapes(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut normalized = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) => normalized.push(byte),
+            Some(byte) => normalized.extend(format!("%{:02X}", byte).into_bytes()),
+            None => {
+                normalized.push(bytes[i]);
+                i += 1;
+                continue;
+            }
+        }
+        i += 3;
+    }
+    // Only ASCII was substituted for ASCII escapes, so the bytes are still UTF-8
+    String::from_utf8(normalized).expect("normalized escapes stay UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equivalent_requests_share_a_digest() {
+        let request = RequestDescription::new("post", "/api//v1/./price/../quote?symbol=BTC&&currency=USD", b"{}");
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/api/v1/quote");
+        assert_eq!(request.query, "currency=USD&symbol=BTC");
+
+        let same = RequestDescription::new("POST", "/api/v1/%71uote?currency=USD&symbol=BTC", b"{}");
+        assert_eq!(same.digest(), request.digest());
+        assert_eq!(normalize_escapes("a%2fb%e2%82%AC"), "a%2Fb%E2%82%AC");
+        assert_eq!(normalize_path("/a/%2E%2E/b/"), "/b/");
+
+        for other in [
+            RequestDescription::new("GET", "/api/v1/quote?currency=USD&symbol=BTC", b"{}"),
+            RequestDescription::new("POST", "/api/v1/quote/?currency=USD&symbol=BTC", b"{}"),
+            RequestDescription::new("POST", "/api/v1/quote?currency=USD&symbol=ETH", b"{}"),
+            RequestDescription::new("POST", "/api/v1/quote?currency=USD&symbol=BTC", b"{\"x\":1}"),
+        ] {
+            assert_ne!(other.digest(), request.digest(), "{}", other.canonical());
+        }
+    }
+
+    #[test]
+    fn test_repeated_query_keys_keep_their_order() {
+        let request = RequestDescription::new("GET", "/quote?symbol=BTC&currency=USD&symbol=ETH", b"");
+        assert_eq!(request.query, "currency=USD&symbol=BTC&symbol=ETH");
+        assert_eq!(request.target(), "/quote?currency=USD&symbol=BTC&symbol=ETH");
+
+        let reordered = RequestDescription::new("GET", "/quote?symbol=ETH&currency=USD&symbol=BTC", b"");
+        assert_eq!(reordered.query, "currency=USD&symbol=ETH&symbol=BTC");
+        assert_ne!(reordered.digest(), request.digest());
+        assert_eq!(RequestDescription::new("GET", "/a/../quote", b"").target(), "/quote");
+    }
+}
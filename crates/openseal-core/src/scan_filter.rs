@@ -0,0 +1,125 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::error::{IdentityError, IdentityResult};
+use crate::preset::ExclusionRules;
+use crate::symlink::SymlinkPolicy;
+use crate::IdentityOptions;
+
+/// Per-directory ignore files honored by the directory walker, highest precedence first.
+pub(crate) const IGNORE_FILES: [&str; 2] = [".opensealignore", ".ignore"];
+
+/// The directory scan's selection rules, for deciding about single paths without walking the project
+/// (e.g. paths reported by a file watcher).
+pub struct ScanFilter {
+    root: std::path::PathBuf,
+    exclusion_rules: ExclusionRules,
+    follow_links: bool,
+}
+
+impl ScanFilter {
+    /// Rules of the project at `root` under `options`. Ignore files are read per check,
+    /// as the walker reads them per directory.
+    pub fn new(root: &Path, options: &IdentityOptions) -> Self {
+        ScanFilter {
+            root: root.to_path_buf(),
+            exclusion_rules: options.exclusion_rules(),
+            follow_links: options.symlinks == SymlinkPolicy::Follow,
+        }
+    }
+
+    /// Whether the scan leaves `relative` out: it, or a directory above it, is excluded or ignored.
+    pub fn is_pruned(&self, relative: &Path) -> IdentityResult<bool> {
+        let mut rule_files = HashMap::new();
+        for dir in relative.ancestors().skip(1) {
+            for name in IGNORE_FILES {
+                let rule_file = dir.join(name);
+                match fs::read_to_string(self.root.join(&rule_file)) {
+                    Ok(content) => { rule_files.insert(rule_file.to_string_lossy().to_string(), content); }
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(IdentityError::io("read", &self.root.join(&rule_file))(e)),
+                }
+            }
+        }
+        let metadata = fs::symlink_metadata(self.root.join(relative)).ok();
+        let is_dir = metadata.as_ref().is_some_and(|metadata| metadata.is_dir());
+        let unfollowed_link = !self.follow_links && metadata.is_some_and(|metadata| metadata.is_symlink());
+        let ignores = IgnoreRules::build(&rule_files)?;
+        Ok(is_pruned(&relative.to_string_lossy(), is_dir, unfollowed_link, &self.exclusion_rules, &ignores))
+    }
+}
+
+/// `.opensealignore` / `.ignore` matchers by directory ("" is the root).
+pub(crate) struct IgnoreRules {
+    by_dir: HashMap<String, Vec<Option<Gitignore>>>,
+}
+
+impl IgnoreRules {
+    pub(crate) fn build(rule_files: &HashMap<String, String>) -> IdentityResult<Self> {
+        let mut by_dir: HashMap<String, Vec<Option<Gitignore>>> = HashMap::new();
+        for (path, content) in rule_files {
+            let path = Path::new(path);
+            let Some(kind) = IGNORE_FILES.iter().position(|name| path.file_name() == Some(name.as_ref())) else {
+                continue;
+            };
+            let dir = path.parent().unwrap_or(Path::new(""));
+            let mut builder = GitignoreBuilder::new(dir);
+            for line in content.lines() {
+                builder.add_line(Some(path.to_path_buf()), line).map_err(IdentityError::pattern(path))?;
+            }
+            let matcher = builder.build().map_err(IdentityError::pattern(path))?;
+            let slots = by_dir.entry(dir.to_string_lossy().to_string())
+                .or_insert_with(|| vec![None; IGNORE_FILES.len()]);
+            slots[kind] = Some(matcher);
+        }
+        Ok(IgnoreRules { by_dir })
+    }
+
+    /// Mirrors the walker: per ignore file kind, the nearest directory with a decisive match wins.
+    pub(crate) fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        for kind in 0..IGNORE_FILES.len() {
+            let mut dir = Path::new(path).parent();
+            while let Some(current) = dir {
+                let matcher = self.by_dir.get(current.to_string_lossy().as_ref())
+                    .and_then(|slots| slots[kind].as_ref());
+                if let Some(matcher) = matcher {
+                    match matcher.matched(path, is_dir) {
+                        Match::Ignore(_) => return true,
+                        Match::Whitelist(_) => return false,
+                        Match::None => {}
+                    }
+                }
+                dir = current.parent();
+            }
+        }
+        false
+    }
+}
+
+/// Whether the walker would skip `path`: an ancestor directory (or the entry itself) is
+/// excluded by name or ignored. Like the walker, the entry's own name is only checked against
+/// the exclusions when it is a directory or a link that is not followed.
+pub(crate) fn is_pruned(
+    path: &str,
+    is_dir: bool,
+    unfollowed_link: bool,
+    exclusion_rules: &ExclusionRules,
+    ignores: &IgnoreRules,
+) -> bool {
+    let mut prefix_len = 0;
+    let components: Vec<&str> = path.split('/').collect();
+    for (i, name) in components.iter().enumerate() {
+        prefix_len += name.len() + usize::from(i > 0);
+        let is_last = i + 1 == components.len();
+        if (!is_last || is_dir || unfollowed_link) && exclusion_rules.rule(name, i + 1).is_some() {
+            return true;
+        }
+        if ignores.is_ignored(&path[..prefix_len], !is_last || is_dir) {
+            return true;
+        }
+    }
+    false
+}
@@ -0,0 +1,96 @@
+use blake3::Hash;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// How symbolic links inside the project are treated when computing an identity.
+/// Links that resolve outside the project root are rejected under every policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SymlinkPolicy {
+    /// Seal the link itself: its leaf commits to the target path, not the target's content.
+    #[default]
+    HashTarget,
+    /// Follow the link and seal the content it points to (as if it were a regular file/directory).
+    Follow,
+    /// Any symlink aborts the computation.
+    Reject,
+}
+
+/// Content hash of a symlink leaf: Hash("SYMLINK_TARGET" || target), with `/` separators.
+pub fn symlink_target_hash(target: &Path) -> Hash {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(b"SYMLINK_TARGET");
+    hasher.update(target.to_string_lossy().replace('\\', "/").as_bytes());
+    hasher.finalize()
+}
+
+/// Returns true if the link at `link_path` resolves outside `canonical_root`.
+/// Existing targets are canonicalized (catching link chains); dangling targets are resolved lexically.
+pub fn escapes_root(canonical_root: &Path, link_path: &Path, target: &Path) -> bool {
+    if let Ok(resolved) = std::fs::canonicalize(link_path) {
+        return !resolved.starts_with(canonical_root);
+    }
+    let parent = link_path.parent().unwrap_or(Path::new(""));
+    let parent = std::fs::canonicalize(parent).unwrap_or_else(|_| parent.to_path_buf());
+    match normalize_lexically(&parent.join(target)) {
+        Some(resolved) => !resolved.starts_with(canonical_root),
+        None => true,
+    }
+}
+
+/// Returns true if a link at `link_rel` (relative to the root) points outside the root,
+/// resolving purely lexically (for trees that are not on disk, such as archives).
+pub fn escapes_root_lexically(link_rel: &Path, target: &Path) -> bool {
+    if target.has_root() {
+        return true;
+    }
+    let parent = link_rel.parent().unwrap_or(Path::new(""));
+    normalize_lexically(&parent.join(target)).is_none()
+}
+
+/// Resolves `.` and `..` without touching the filesystem. Returns None if `..` climbs above the start.
+pub(crate) fn normalize_lexically(path: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    return None;
+                }
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    Some(out)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_escape_detection() -> anyhow::Result<()> {
+        let outside = tempdir()?;
+        let dir = tempdir()?;
+        let root = std::fs::canonicalize(dir.path())?;
+        std::fs::create_dir(root.join("lib"))?;
+        std::fs::write(root.join("lib/real.txt"), "x")?;
+
+        symlink("lib/real.txt", root.join("inside"))?;
+        symlink(outside.path(), root.join("absolute_out"))?;
+        symlink("../../etc/passwd", root.join("lib/relative_out"))?;
+        symlink("../missing", root.join("lib/dangling_in"))?;
+        // A chain that only escapes through a second link
+        symlink("absolute_out", root.join("chained"))?;
+
+        assert!(!escapes_root(&root, &root.join("inside"), Path::new("lib/real.txt")));
+        assert!(escapes_root(&root, &root.join("absolute_out"), outside.path()));
+        assert!(escapes_root(&root, &root.join("lib/relative_out"), Path::new("../../etc/passwd")));
+        assert!(!escapes_root(&root, &root.join("lib/dangling_in"), Path::new("../missing")));
+        assert!(escapes_root(&root, &root.join("chained"), Path::new("absolute_out")));
+        Ok(())
+    }
+}
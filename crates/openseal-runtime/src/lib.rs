@@ -1,82 +1,236 @@
 use axum::{
     body::Body,
     extract::{Request, State},
-    http::{HeaderValue, StatusCode},
+    http::{header::AUTHORIZATION, HeaderMap, HeaderName, HeaderValue, StatusCode},
     response::IntoResponse,
-    routing::any,
+    routing::{any, post},
     Router,
 };
-use openseal_core::{compute_a_hash, compute_project_identity, ProjectIdentity};
+use openseal_core::{
+    compute_a_hash, compute_lock_manifest_with_options, compute_project_identity_cached, diff_manifests,
+    CanonicalProfile, Digest, DigestAlgorithm, HashCache, IdentityOptions, IdentityScheme, LanguagePreset, LockManifest, ProgressCallback,
+    ProjectIdentity, RequestDescription, SealMode, SealPayload, SealProtocol, SealSuite, SealedHeaders,
+    LOCK_FILE_NAME,
+};
 use openseal_secret::compute_b_hash;
+use watch::IntegrityMonitor;
 use rand::{rngs::OsRng, RngCore};
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::net::TcpListener;
 use ed25519_dalek::{SigningKey, Signer};
 use anyhow::{anyhow, Context};
+use chrono::{DateTime, Utc};
 use std::io::{self, Write};
 use std::process::Command;
 
+pub mod watch;
+
+/// Default `OPENSEAL_REATTEST_INTERVAL`, in seconds.
+const DEFAULT_REATTEST_INTERVAL_SECS: u64 = 600;
+
+/// Default `OPENSEAL_SEALED_HEADERS`.
+const DEFAULT_SEALED_HEADERS: &str = "content-type,etag";
+
 #[derive(Clone)]
 struct AppState {
     target_url: String,
     project_identity: ProjectIdentity,
     signing_key: SigningKey,
+    integrity: Arc<IntegrityMonitor>,
+    /// Bearer token of the admin endpoints (`OPENSEAL_ADMIN_TOKEN`); unset disables them.
+    admin_token: Option<String>,
+    /// Lifetime of issued seals (`OPENSEAL_SEAL_TTL`); unset issues seals without `expires_at`.
+    seal_ttl: Option<chrono::Duration>,
+    /// Upstream response headers bound into every seal (`OPENSEAL_SEALED_HEADERS`).
+    sealed_headers: Vec<HeaderName>,
 }
 
+/// Verifies the project against its openseal.json and returns its identity,
+/// with the time the verifying scan started (the first attestation).
 pub async fn prepare_runtime(
-    project_root: &PathBuf,
+    project_root: &Path,
     dependency_hint: Option<String>,
-) -> anyhow::Result<ProjectIdentity> {
+) -> anyhow::Result<(ProjectIdentity, DateTime<Utc>)> {
     println!("🔐 OpenSeal Runtime v{} Initializing...", env!("CARGO_PKG_VERSION"));
     
-    // 1. Static Commitment: Compute A-hash at startup
-    let live_identity = compute_project_identity(&project_root)?;
+    // 1. Load Expected Identity from openseal.json (if sealed)
+    let manifest = load_manifest(project_root)?;
+
+    // 2. Static Commitment: Compute A-hash at startup
+    // Production re-hashes every byte and neither reads nor writes the cache (strict);
+    // Development reuses hashes of unchanged files.
+    let production = SealMode::from_env() == SealMode::Production;
+    let mut hash_cache = match HashCache::default_path(project_root) {
+        Some(cache_path) if !production => HashCache::load(&cache_path, project_root),
+        _ => HashCache::in_memory(),
+    };
+    hash_cache.set_strict(production);
+    let mut options = identity_options(project_root, manifest.as_ref())?;
+    options.progress = Some(startup_progress());
+    let attested_at = Utc::now();
+    let (live_identity, hash_cache) = compute_identity_blocking(project_root, options.clone(), hash_cache).await?;
+    if let Err(e) = hash_cache.save() {
+        eprintln!("   ⚠️  Failed to persist hash cache: {}", e);
+    }
     
-    // 2. Load Expected Hash from openseal.json
-    let manifest_path = project_root.join("openseal.json");
-    if manifest_path.exists() {
-        let manifest_content = std::fs::read_to_string(&manifest_path)?;
-        let manifest: serde_json::Value = serde_json::from_str(&manifest_content)?;
-        
-        if let Some(expected_hash_array) = manifest["identity"]["root_hash"].as_array() {
-            let expected_hash_bytes: Vec<u8> = expected_hash_array
-                .iter()
-                .filter_map(|v| v.as_u64().map(|n| n as u8))
-                .collect();
-            
-            if live_identity.root_hash.as_bytes() != expected_hash_bytes.as_slice() {
+    if let Some(manifest) = manifest {
+        if let Some(expected_hash) = recorded_root(&manifest)? {
+            if expected_hash.algorithm() != DigestAlgorithm::Blake3 {
+                // Image digest written by `openseal build`: `openseal run` checks it against the image
+                println!("   ℹ️  Image Identity: {} (not a project root, checked by 'openseal run')", expected_hash);
+            } else if live_identity.root_hash != expected_hash {
                 eprintln!("\n🚨 ═══════════════════════════════════════════════════════════");
                 eprintln!("   CRITICAL: INTEGRITY VIOLATION DETECTED");
                 eprintln!("   ═══════════════════════════════════════════════════════════");
                 eprintln!("   The sealed bundle has been modified!");
                 eprintln!("   ");
-                eprintln!("   Expected Hash: {}", hex::encode(&expected_hash_bytes));
-                eprintln!("   Actual Hash:   {}", live_identity.root_hash.to_hex());
+                eprintln!("   Expected Hash: {}", expected_hash);
+                eprintln!("   Actual Hash:   {}", live_identity.root_hash);
                 eprintln!("   ");
+                print_integrity_diff(project_root, &options);
                 eprintln!("   This runtime will NOT start for security reasons.");
                 eprintln!("   Please rebuild with 'openseal build' to restore integrity.");
                 eprintln!("   ═══════════════════════════════════════════════════════════\n");
                 
                 return Err(anyhow!("Integrity violation detected - Runtime aborted"));
+            } else {
+                println!("   ✅ Integrity Verified!");
             }
-            println!("   ✅ Integrity Verified!");
         }
     }
 
     // 3. Dependency Management
     handle_dependencies(project_root, dependency_hint).await?;
     
-    Ok(live_identity)
+    Ok((live_identity, attested_at))
+}
+
+/// openseal.json of a sealed project, or None for an unsealed one.
+fn load_manifest(project_root: &Path) -> anyhow::Result<Option<serde_json::Value>> {
+    let manifest_path = project_root.join("openseal.json");
+    if !manifest_path.exists() {
+        return Ok(None);
+    }
+    let manifest_content = std::fs::read_to_string(&manifest_path)?;
+    Ok(Some(serde_json::from_str(&manifest_content)?))
+}
+
+/// `identity.root_hash` of openseal.json: `blake3:<hex>` / `sha256:<hex>`, bare hex or a byte array (BLAKE3).
+fn recorded_root(manifest: &serde_json::Value) -> anyhow::Result<Option<Digest>> {
+    manifest["identity"].get("root_hash")
+        .map(|value| serde_json::from_value(value.clone()).context("Invalid identity.root_hash in openseal.json"))
+        .transpose()
+}
+
+/// The options a project is verified with: the scheme, preset and canonical profile
+/// recorded in its openseal.json, or the current defaults for an unsealed project.
+fn identity_options(project_root: &Path, manifest: Option<&serde_json::Value>) -> anyhow::Result<IdentityOptions> {
+    // Recompute under the scheme the bundle was sealed with
+    let scheme = match manifest.map(|m| (m, &m["identity"]["scheme"])) {
+        None => IdentityScheme::CURRENT,
+        Some((manifest, serde_json::Value::Null)) => match recorded_root(manifest)? {
+            // Written before identities were Merkle roots: that flat root matches no scheme
+            Some(root) if root.algorithm() == DigestAlgorithm::Blake3 => return Err(anyhow!(
+                "openseal.json records a project root without an identity scheme ({}). \
+                 It was sealed by an earlier version whose flat root can't be recomputed: \
+                 re-seal required, please rebuild with 'openseal build'",
+                root
+            )),
+            // Image digest only: no project root to recompute
+            _ => IdentityScheme::CURRENT,
+        },
+        Some((_, value)) => serde_json::from_value(value.clone())
+            .context("Unsupported identity scheme in openseal.json")?,
+    };
+
+    // Same file set as at sealing time: recorded preset, or detection for unsealed projects
+    let preset = match manifest.map(|m| &m["identity"]["preset"]) {
+        None => Some(LanguagePreset::detect(project_root)),
+        Some(serde_json::Value::Null) => None,
+        Some(value) => Some(serde_json::from_value(value.clone())
+            .context("Unsupported identity preset in openseal.json")?),
+    };
+
+    // Paths and line endings normalized as at sealing time (native unless recorded)
+    let canonical = match manifest.map(|m| &m["identity"]["canonical"]) {
+        None | Some(serde_json::Value::Null) => CanonicalProfile::Native,
+        Some(value) => serde_json::from_value(value.clone())
+            .context("Unsupported canonical profile in openseal.json")?,
+    };
+
+    let mut options = IdentityOptions { scheme, preset, canonical, ..Default::default() };
+    if project_root.join("node_modules").is_symlink() {
+        // Dependency link created by handle_dependencies (points outside the sealed bundle)
+        options.exclude_dirs.push("node_modules".to_string());
+    }
+    Ok(options)
+}
+
+/// Hashes the project on tokio's blocking pool so the async workers stay free.
+/// Ctrl-C during the scan cancels it instead of waiting for the last file.
+async fn compute_identity_blocking(
+    project_root: &Path,
+    options: IdentityOptions,
+    mut hash_cache: HashCache,
+) -> anyhow::Result<(ProjectIdentity, HashCache)> {
+    let cancel = options.cancel.clone();
+    let root = project_root.to_path_buf();
+    let scan = tokio::task::spawn_blocking(move || {
+        compute_project_identity_cached(&root, &options, &mut hash_cache).map(|identity| (identity, hash_cache))
+    });
+    tokio::pin!(scan);
+    let result = tokio::select! {
+        result = &mut scan => result,
+        _ = tokio::signal::ctrl_c() => {
+            cancel.cancel();
+            scan.await
+        }
+    };
+    Ok(result.context("Identity computation panicked")??)
+}
+
+/// Prints scan progress at most once per interval, so a large bundle doesn't look like a hung startup.
+fn startup_progress() -> ProgressCallback {
+    const INTERVAL: Duration = Duration::from_secs(2);
+    let last_report = Mutex::new(Instant::now());
+    ProgressCallback::new(move |progress| {
+        // Another hashing thread is reporting right now
+        let Ok(mut last) = last_report.try_lock() else { return };
+        if last.elapsed() < INTERVAL {
+            return;
+        }
+        *last = Instant::now();
+        if progress.discovery_complete {
+            println!("   ⏳ Hashed {}/{} files ({} MiB)",
+                progress.files_hashed, progress.files_discovered, progress.bytes_hashed / (1024 * 1024));
+        } else {
+            println!("   ⏳ Discovered {} files...", progress.files_discovered);
+        }
+    })
 }
 
 pub async fn run_proxy_server(
     port: u16, 
     target_url: String, 
-    _project_root: PathBuf,
+    project_root: PathBuf,
     project_identity: ProjectIdentity,
+    attested_at: DateTime<Utc>,
 ) -> anyhow::Result<()> {
 
+    // Watch and re-attest the sealed files of a project identity (image digests pin their files already)
+    let integrity = IntegrityMonitor::new(attested_at);
+    if let Some(sealed_root) = project_identity.root_hash.as_blake3() {
+        let options = identity_options(&project_root, load_manifest(&project_root)?.as_ref())?;
+        let interval = reattest_interval()?;
+        match interval {
+            Some(interval) => println!("   🔁 Re-attesting every {}s", interval.as_secs()),
+            None => println!("   🔁 Periodic re-attestation disabled"),
+        }
+        watch::spawn_watcher(&project_root, options, sealed_root, interval, Arc::clone(&integrity))?;
+    }
+
     // Generate a strictly ephemeral signing key for this runtime session (Mandatory in v2.0)
     let mut csprng = OsRng;
     let mut key_bytes = [0u8; 32];
@@ -89,10 +243,15 @@ pub async fn run_proxy_server(
         target_url,
         project_identity,
         signing_key: key,
+        integrity,
+        admin_token: std::env::var("OPENSEAL_ADMIN_TOKEN").ok().filter(|token| !token.is_empty()),
+        seal_ttl: seal_ttl()?,
+        sealed_headers: sealed_headers()?,
     });
 
     let app = Router::new()
         .route("/.openseal/identity", any(identity_handler))
+        .route("/.openseal/reattest", post(reattest_handler))
         .route("/*path", any(handler))
         .with_state(state);
 
@@ -106,6 +265,62 @@ pub async fn run_proxy_server(
     Ok(())
 }
 
+/// Interval of the background re-attestation: `OPENSEAL_REATTEST_INTERVAL` seconds
+/// (default 600), or None when set to 0.
+fn reattest_interval() -> anyhow::Result<Option<Duration>> {
+    let seconds = match std::env::var("OPENSEAL_REATTEST_INTERVAL") {
+        Ok(value) => value.trim().parse::<u64>()
+            .with_context(|| format!("Invalid OPENSEAL_REATTEST_INTERVAL '{}' (seconds, 0 disables)", value))?,
+        Err(_) => DEFAULT_REATTEST_INTERVAL_SECS,
+    };
+    Ok((seconds > 0).then(|| Duration::from_secs(seconds)))
+}
+
+/// `OPENSEAL_SEAL_TTL`, in seconds: how long after issuance a seal expires (unset or 0: never).
+fn seal_ttl() -> anyhow::Result<Option<chrono::Duration>> {
+    let Ok(value) = std::env::var("OPENSEAL_SEAL_TTL") else {
+        return Ok(None);
+    };
+    let seconds = value.trim().parse::<u32>()
+        .with_context(|| format!("Invalid OPENSEAL_SEAL_TTL '{}' (seconds, 0 disables)", value))?;
+    Ok((seconds > 0).then(|| chrono::Duration::seconds(seconds.into())))
+}
+
+/// `OPENSEAL_SEALED_HEADERS`: comma-separated upstream response headers signed into every seal
+/// (default `content-type,etag`; empty seals none).
+fn sealed_headers() -> anyhow::Result<Vec<HeaderName>> {
+    let names = std::env::var("OPENSEAL_SEALED_HEADERS").unwrap_or_else(|_| DEFAULT_SEALED_HEADERS.to_string());
+    names.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| HeaderName::from_bytes(name.as_bytes())
+            .with_context(|| format!("Invalid header name '{}' in OPENSEAL_SEALED_HEADERS", name)))
+        .collect()
+}
+
+/// Prints which files differ from the sealed lock manifest (if the bundle ships one).
+fn print_integrity_diff(project_root: &Path, options: &IdentityOptions) {
+    let lock_path = project_root.join(LOCK_FILE_NAME);
+    if !lock_path.exists() {
+        eprintln!("   (No {} found - cannot list changed files)", LOCK_FILE_NAME);
+        eprintln!("   ");
+        return;
+    }
+
+    let diff = LockManifest::load(&lock_path)
+        .and_then(|expected| Ok(diff_manifests(&expected, &compute_lock_manifest_with_options(project_root, options)?)));
+    match diff {
+        Ok(diff) => {
+            eprintln!("   Changed Files ({}):", diff.change_count());
+            for line in diff.to_string().lines() {
+                eprintln!("     {}", line);
+            }
+        }
+        Err(e) => eprintln!("   (Failed to diff against {}: {})", LOCK_FILE_NAME, e),
+    }
+    eprintln!("   ");
+}
+
 async fn handle_dependencies(
     project_root: &std::path::Path,
     dependency_hint: Option<String>,
@@ -296,20 +511,66 @@ fn install_pip_dependencies(project_root: &std::path::Path) -> anyhow::Result<()
 async fn identity_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     // Simple identity response without requiring Wax challenge
     // This is a public, read-only endpoint for discovery
-    let identity_response = serde_json::json!({
+    let mut identity_response = serde_json::json!({
         "service": "OpenSeal Runtime Identity",
         "version": "0.2.0",
         "identity": {
-            "a_hash": state.project_identity.root_hash.to_hex().to_string(),
+            "a_hash": state.project_identity.root_hash.to_string(),
             "file_count": state.project_identity.file_count,
         },
         "status": "sealed"
     });
+    identity_response["last_attested_at"] = serde_json::json!(state.integrity.last_attested().to_rfc3339());
+    if let Some(drift) = state.integrity.drift() {
+        identity_response["status"] = serde_json::json!("integrity_violation");
+        identity_response["drift"] = serde_json::json!(drift);
+    }
 
     (StatusCode::OK, axum::Json(identity_response)).into_response()
 }
 
+/// Handler for POST /.openseal/reattest (admin)
+/// Schedules a full re-attestation; requires `Authorization: Bearer <OPENSEAL_ADMIN_TOKEN>`.
+async fn reattest_handler(State(state): State<Arc<AppState>>, headers: HeaderMap) -> impl IntoResponse {
+    let Some(admin_token) = &state.admin_token else {
+        return (StatusCode::NOT_FOUND, "Admin endpoints are disabled (set OPENSEAL_ADMIN_TOKEN)").into_response();
+    };
+    let presented = headers.get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    // Hash comparison is constant-time
+    if blake3::hash(presented.as_bytes()) != blake3::hash(admin_token.as_bytes()) {
+        return (StatusCode::UNAUTHORIZED, "Invalid admin token").into_response();
+    }
+
+    let last_attested_at = state.integrity.last_attested().to_rfc3339();
+    if let Some(drift) = state.integrity.drift() {
+        let response = serde_json::json!({ "status": "integrity_violation", "last_attested_at": last_attested_at, "drift": drift });
+        return (StatusCode::CONFLICT, axum::Json(response)).into_response();
+    }
+    if state.project_identity.root_hash.as_blake3().is_none() {
+        return (StatusCode::CONFLICT, "Image-based identities are not re-attested").into_response();
+    }
+    if !state.integrity.request_reattestation() {
+        let response = serde_json::json!({ "status": "reattestation_unavailable", "last_attested_at": last_attested_at });
+        return (StatusCode::SERVICE_UNAVAILABLE, axum::Json(response)).into_response();
+    }
+    let response = serde_json::json!({ "status": "reattestation_scheduled", "last_attested_at": last_attested_at });
+    (StatusCode::ACCEPTED, axum::Json(response)).into_response()
+}
+
 async fn handler(State(state): State<Arc<AppState>>, req: Request<Body>) -> impl IntoResponse {
+    // 1. Refuse to seal once the project drifted from the verified identity
+    if let Some(drift) = state.integrity.drift() {
+        let failure = serde_json::json!({
+            "error": "integrity_violation",
+            "message": "The sealed project changed after startup; responses are no longer sealed.",
+            "drift": drift,
+        });
+        return (StatusCode::SERVICE_UNAVAILABLE, axum::Json(failure)).into_response();
+    }
+
     let client = reqwest::Client::new();
     
     // 2. Dynamic Trajectory: Extract Wax (Challenge/Context) from Header
@@ -325,15 +586,14 @@ async fn handler(State(state): State<Arc<AppState>>, req: Request<Body>) -> impl
 
     // Prepare A-hash
     // Prepare Blinded A-hash
-    let a_hash = compute_a_hash(&state.project_identity.root_hash, &wax_hex);
+    let a_hash = compute_a_hash(&state.project_identity.root_hash, &wax_hex, SealProtocol::CURRENT);
     let a_hash_hex = a_hash.to_hex().to_string();
 
     // 3. Execution Interception (Call Boundary)
     // Construct the internal request
     let path = req.uri().path().to_string();
-    let query = req.uri().query().map(|q| format!("?{}", q)).unwrap_or_default();
-    let target_uri = format!("{}{}{}", state.target_url, path, query);
-    
+    let raw_query = req.uri().query().unwrap_or_default().to_string();
+
     let method = req.method().clone();
     let mut headers = req.headers().clone();
 
@@ -344,6 +604,12 @@ async fn handler(State(state): State<Arc<AppState>>, req: Request<Body>) -> impl
     // Extract body to forward
     let body_bytes = axum::body::to_bytes(req.into_body(), usize::MAX).await.unwrap_or_default();
 
+    // The request this result answers (signed, so the seal can't be replayed for another request).
+    // The normalized target is what gets forwarded, so the digest covers what the app actually received.
+    let request = RequestDescription::from_parts(method.as_str(), &path, &raw_query, blake3::hash(&body_bytes));
+    let request_digest = request.digest().to_hex().to_string();
+    let target_uri = format!("{}{}", state.target_url, request.target());
+
     // Call the Internal Logic (The Case)
     let response_result = client
         .request(method, &target_uri)
@@ -355,7 +621,16 @@ async fn handler(State(state): State<Arc<AppState>>, req: Request<Body>) -> impl
     match response_result {
         Ok(resp) => {
             // 4. Result Capture (Egress Interception)
-            let _status = resp.status();
+            // Bound into the seal, so an error page can't pass for a success (absent headers are signed as null)
+            let status = resp.status();
+            let sealed_headers: SealedHeaders = state.sealed_headers.iter()
+                .map(|name| {
+                    let values: Vec<String> = resp.headers().get_all(name).iter()
+                        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+                        .collect();
+                    (name.as_str().to_string(), (!values.is_empty()).then(|| values.join(", ")))
+                })
+                .collect();
             let resp_bytes = resp.bytes().await.unwrap_or_default();
 
             // 5b. Standardization (Canonicalization Attempt)
@@ -376,11 +651,30 @@ async fn handler(State(state): State<Arc<AppState>>, req: Request<Body>) -> impl
             let b_hash_hex = b_hash.to_hex().to_string();
 
             // 6. Optional Sign the Seal
-            // Signature = Sign(Wax || A || B || SHA256(Result))
-            let result_hash = blake3::hash(standardized_bytes).to_hex().to_string();
+            // Signature = Sign(Protocol, Suite, Wax, A, B, Hash(Result), Attested At, Issued At, Expires At, Request Digest, Status, Headers)
+            let result_hash = SealSuite::CURRENT.result_hash(standardized_bytes);
+
+            // Signed, so verifiers can trust how fresh the integrity check behind this seal is
+            let attested_at = state.integrity.last_attested().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+            // Signed, so archived responses can prove when they were produced and until when they hold
+            let issued = chrono::Utc::now();
+            let issued_at = issued.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+            let expires_at = state.seal_ttl
+                .map(|ttl| (issued + ttl).to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
             
-            let sign_payload = format!("{}{}{}{}", wax_hex, a_hash_hex, b_hash_hex, result_hash);
-            let sig = state.signing_key.sign(sign_payload.as_bytes());
+            let sign_payload = SealPayload {
+                wax: &wax_hex,
+                a_hash: &a_hash_hex,
+                b_hash: &b_hash_hex,
+                result_hash: &result_hash,
+                attested_at: Some(&attested_at),
+                issued_at: Some(&issued_at),
+                expires_at: expires_at.as_deref(),
+                request_digest: Some(&request_digest),
+                status: Some(status.as_u16()),
+                headers: Some(&sealed_headers),
+            };
+            let sig = state.signing_key.sign(&sign_payload.message(SealProtocol::CURRENT, SealSuite::CURRENT));
             let pub_key_hex = hex::encode(state.signing_key.verifying_key().to_bytes());
             
             // let signature = Some(hex::encode(sig.to_bytes()));
@@ -392,20 +686,36 @@ async fn handler(State(state): State<Arc<AppState>>, req: Request<Body>) -> impl
                 openseal_core::SealMode::Development => {
                     // Full Seal with all debugging information
                     openseal_core::Seal {
+                        protocol: SealProtocol::CURRENT,
+                        suite: SealSuite::CURRENT,
                         signature: hex::encode(sig.to_bytes()),
                         // wax is known to caller, no need to return
                         pub_key: Some(pub_key_hex),
                         a_hash: Some(a_hash_hex),
                         b_hash: Some(b_hash_hex),
+                        attested_at: Some(attested_at),
+                        issued_at: Some(issued_at),
+                        expires_at,
+                        request_digest: Some(request_digest),
+                        status: Some(status.as_u16()),
+                        headers: Some(sealed_headers),
                     }
                 },
                 openseal_core::SealMode::Production => {
                     // Signature-only for maximum security
                     openseal_core::Seal {
+                        protocol: SealProtocol::CURRENT,
+                        suite: SealSuite::CURRENT,
                         signature: hex::encode(sig.to_bytes()),
                         pub_key: Some(pub_key_hex), // Required for verification
                         a_hash: Some(a_hash_hex),   // Identity identifier (Public)
                         b_hash: Some(b_hash_hex),   // Binding identifier (Public, opaque)
+                        attested_at: Some(attested_at),
+                        issued_at: Some(issued_at),
+                        expires_at,
+                        request_digest: Some(request_digest),
+                        status: Some(status.as_u16()),
+                        headers: Some(sealed_headers),
                     }
                 }
             };
@@ -421,7 +731,8 @@ async fn handler(State(state): State<Arc<AppState>>, req: Request<Body>) -> impl
                 "openseal": seal
             });
             
-            (StatusCode::OK, axum::Json(final_response)).into_response()
+            // Same status as the upstream (sealed above)
+            (status, axum::Json(final_response)).into_response()
         }
         Err(e) => {
             let error_msg = format!("Internal Application Error: {}", e);
@@ -429,3 +740,45 @@ async fn handler(State(state): State<Arc<AppState>>, req: Request<Body>) -> impl
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unversioned_project_root_requires_reseal() {
+        let dir = tempfile::tempdir().unwrap();
+        let bytes: Vec<u8> = blake3::hash(b"flat").as_bytes().to_vec();
+        for root_hash in [serde_json::json!(bytes), serde_json::json!(blake3::hash(b"flat").to_hex().to_string())] {
+            let manifest = serde_json::json!({ "identity": { "root_hash": root_hash } });
+            let err = identity_options(dir.path(), Some(&manifest)).unwrap_err();
+            assert!(err.to_string().contains("re-seal required"), "{}", err);
+        }
+
+        // Image digests carry no project root, and versioned manifests recompute under their scheme
+        let image = serde_json::json!({ "identity": { "root_hash": format!("sha256:{}", "ab".repeat(32)) } });
+        assert_eq!(identity_options(dir.path(), Some(&image)).unwrap().scheme, IdentityScheme::CURRENT);
+        let v1 = serde_json::json!({ "identity": { "root_hash": blake3::hash(b"v1").to_hex().to_string(), "scheme": "v1" } });
+        assert_eq!(identity_options(dir.path(), Some(&v1)).unwrap().scheme, IdentityScheme::V1);
+    }
+
+    #[test]
+    fn test_dynamic_b_hash_binding() {
+        let a_hash = blake3::hash(b"PROJECT_IDENTITY");
+        let wax1 = "WAX_1";
+        let wax2 = "WAX_2";
+        let result = b"Execution Result";
+
+        let b1 = compute_b_hash(&a_hash, wax1, result);
+        let b2 = compute_b_hash(&a_hash, wax2, result);
+
+        // Different waxes should produce different B-hashes even if A and Result are same
+        assert_ne!(b1, b2);
+
+        let a_hash_modified = blake3::hash(b"PROJECT_IDENTITY_MODIFIED");
+        let b3 = compute_b_hash(&a_hash_modified, wax1, result);
+
+        // Different A-hash should produce different B-hashes
+        assert_ne!(b1, b3);
+    }
+}
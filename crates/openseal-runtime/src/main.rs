@@ -24,7 +24,7 @@ async fn main() -> anyhow::Result<()> {
     
     // In stand-alone mode, we assume no dependency hint for now 
     // or we could add it to Args.
-    let project_identity = openseal_runtime::prepare_runtime(&args.project_root, None).await?;
+    let (project_identity, attested_at) = openseal_runtime::prepare_runtime(&args.project_root, None).await?;
     
-    run_proxy_server(args.port, args.target, args.project_root, project_identity).await
+    run_proxy_server(args.port, args.target, args.project_root, project_identity, attested_at).await
 }
@@ -0,0 +1,424 @@
+//! Post-startup integrity watch: sealed files are re-hashed as soon as the filesystem reports
+//! a change (inotify on Linux), the whole project is re-attested periodically and on demand,
+//! and the runtime stops sealing once the project drifts.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use notify::{EventKind, RecursiveMode, Watcher};
+use openseal_core::{
+    compute_lock_manifest_with_options, diff_manifests, merkle, symlink, IdentityOptions, LeafMeta,
+    LockEntry, LockManifest, ScanFilter, SymlinkPolicy,
+};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, Notify};
+use tokio::time::Interval;
+
+/// Quiet period after a filesystem event before the batch is checked (editors write in bursts).
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftChange {
+    Added,
+    Removed,
+    /// Content, mode or file type changed.
+    Modified,
+}
+
+/// A sealed file that no longer matches the identity verified at startup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DriftEvent {
+    /// Path relative to the project root, as recorded in the identity.
+    pub path: String,
+    pub change: DriftChange,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Shared between the watcher and the request handlers. Once drift is reported the
+/// runtime stays unsealed until it is restarted (and re-verified) by an operator.
+#[derive(Debug)]
+pub struct IntegrityMonitor {
+    drift: RwLock<Option<Vec<DriftEvent>>>,
+    /// Start of the last full re-hash that matched the sealed root (startup verification included).
+    last_attested: RwLock<DateTime<Utc>>,
+    reattest: Notify,
+    /// Whether the watcher task is running (and listening for re-attestation requests).
+    watching: AtomicBool,
+}
+
+impl IntegrityMonitor {
+    /// A monitor for an identity verified at `attested_at`.
+    pub fn new(attested_at: DateTime<Utc>) -> Arc<Self> {
+        Arc::new(IntegrityMonitor {
+            drift: RwLock::default(),
+            last_attested: RwLock::new(attested_at),
+            reattest: Notify::new(),
+            watching: AtomicBool::new(false),
+        })
+    }
+
+    pub fn last_attested(&self) -> DateTime<Utc> {
+        *self.last_attested.read().unwrap()
+    }
+
+    /// Asks the watcher for a full re-attestation now (in the background).
+    /// Returns false when no watcher is running to perform it.
+    pub fn request_reattestation(&self) -> bool {
+        if !self.is_watching() {
+            return false;
+        }
+        self.reattest.notify_one();
+        true
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.watching.load(Ordering::SeqCst)
+    }
+
+    /// The changes that broke the seal, if any.
+    pub fn drift(&self) -> Option<Vec<DriftEvent>> {
+        self.drift.read().unwrap().clone()
+    }
+
+    pub fn is_intact(&self) -> bool {
+        self.drift.read().unwrap().is_none()
+    }
+
+    /// Records the first drift and prints the operator event. Later reports are ignored.
+    pub fn report_drift(&self, events: Vec<DriftEvent>) {
+        let mut drift = self.drift.write().unwrap();
+        if drift.is_some() || events.is_empty() {
+            return;
+        }
+        eprintln!("\n🚨 ═══════════════════════════════════════════════════════════");
+        eprintln!("   CRITICAL: INTEGRITY DRIFT DETECTED AT RUNTIME");
+        eprintln!("   ═══════════════════════════════════════════════════════════");
+        eprintln!("   Sealed files changed after startup:");
+        for event in &events {
+            eprintln!("     {:?} {} ({})", event.change, event.path, event.detected_at.to_rfc3339());
+        }
+        eprintln!("   ");
+        eprintln!("   Sealing is disabled: every request now gets an integrity-failure response.");
+        eprintln!("   Restore the sealed files (or rebuild) and restart the runtime.");
+        eprintln!("   ═══════════════════════════════════════════════════════════\n");
+        *drift = Some(events);
+    }
+}
+
+/// Starts watching `project_root` for changes to the files of the identity with root `sealed_root`,
+/// and re-attests the whole project every `reattest_interval` and on `request_reattestation`.
+/// The baseline is re-computed in the background; if it already differs from `sealed_root`
+/// (a change between startup and the watch) or can't be computed, that is reported as drift as well.
+pub fn spawn_watcher(
+    project_root: &Path,
+    options: IdentityOptions,
+    sealed_root: blake3::Hash,
+    reattest_interval: Option<Duration>,
+    monitor: Arc<IntegrityMonitor>,
+) -> anyhow::Result<()> {
+    let root = fs::canonicalize(project_root)
+        .with_context(|| format!("Failed to resolve project root {:?}", project_root))?;
+
+    // Subscribe before the baseline is taken, so no change can fall between the two
+    let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        match result {
+            Ok(event) if is_change(&event.kind) => {
+                for path in event.paths {
+                    let _ = events_tx.send(path);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("   ⚠️  Filesystem watch error: {}", e),
+        }
+    })
+    .context("Failed to create filesystem watcher")?;
+    watcher.configure(notify::Config::default().with_follow_symlinks(false))?;
+    watcher.watch(&root, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {:?}", root))?;
+    println!("   👁️  Watching {:?} for changes to sealed files", root);
+
+    let alive = WatchGuard::start(Arc::clone(&monitor));
+    tokio::spawn(async move {
+        // Dropping the watcher ends the subscription, dropping the guard (on return or panic) marks it stopped
+        let _watcher = watcher;
+        let _alive = alive;
+        let baseline = {
+            let (root, options) = (root.clone(), options.clone());
+            tokio::task::spawn_blocking(move || compute_lock_manifest_with_options(&root, &options)).await
+        };
+        // Without a baseline nothing is watched or re-attested, so stop sealing instead
+        // Without a baseline nothing is watched or re-attested, so stop sealing instead
+        let baseline = match baseline {
+            Ok(Ok(baseline)) => baseline,
+            Ok(Err(e)) => {
+                eprintln!("   ⚠️  Integrity watch failed to start: {:#}", e);
+                return monitor.report_drift(vec![whole_project_drift()]);
+            }
+            Err(e) => {
+                eprintln!("   ⚠️  Integrity watch failed to start: {}", e);
+                return monitor.report_drift(vec![whole_project_drift()]);
+            }
+        };
+        if baseline.root_hash != sealed_root {
+            return monitor.report_drift(vec![whole_project_drift()]);
+        }
+        let check = Arc::new(DriftCheck::new(root, options, baseline));
+        let mut ticker = reattest_interval
+            .map(|period| tokio::time::interval_at(tokio::time::Instant::now() + period, period));
+
+        loop {
+            let check = Arc::clone(&check);
+            let events = tokio::select! {
+                Some(first) = events_rx.recv() => {
+                    let mut paths = BTreeSet::from([first]);
+                    while let Ok(Some(path)) = tokio::time::timeout(DEBOUNCE, events_rx.recv()).await {
+                        paths.insert(path);
+                    }
+                    tokio::task::spawn_blocking(move || check.check_paths(&paths)).await
+                }
+                _ = monitor.reattest.notified() => reattest(check, &monitor).await,
+                _ = tick(&mut ticker) => reattest(check, &monitor).await,
+            };
+            match events {
+                Ok(Ok(events)) if events.is_empty() => {}
+                Ok(Ok(events)) => return monitor.report_drift(events),
+                Ok(Err(e)) => {
+                    // A sealed file that can't be read can't be attested either
+                    eprintln!("   ⚠️  Integrity check failed: {:#}", e);
+                    return monitor.report_drift(vec![whole_project_drift()]);
+                }
+                Err(e) => eprintln!("   ⚠️  Integrity check failed: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Marks the monitor as watched for as long as it lives.
+struct WatchGuard(Arc<IntegrityMonitor>);
+
+impl WatchGuard {
+    fn start(monitor: Arc<IntegrityMonitor>) -> Self {
+        monitor.watching.store(true, Ordering::SeqCst);
+        WatchGuard(monitor)
+    }
+}
+
+impl Drop for WatchGuard {
+    fn drop(&mut self) {
+        self.0.watching.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Re-hashes the whole project and, when it still matches, moves the attestation time forward.
+async fn reattest(
+    check: Arc<DriftCheck>,
+    monitor: &IntegrityMonitor,
+) -> Result<anyhow::Result<Vec<DriftEvent>>, tokio::task::JoinError> {
+    let started = Utc::now();
+    let events = tokio::task::spawn_blocking(move || check.rescan()).await;
+    if let Ok(Ok(events)) = &events {
+        if events.is_empty() {
+            *monitor.last_attested.write().unwrap() = started;
+            println!("   🔁 Re-attestation passed ({})", started.to_rfc3339());
+        }
+    }
+    events
+}
+
+/// Next tick of the re-attestation interval, or never when it is disabled.
+async fn tick(ticker: &mut Option<Interval>) {
+    match ticker {
+        Some(ticker) => { ticker.tick().await; }
+        None => std::future::pending().await,
+    }
+}
+
+/// Drift that can't be pinned to a file (the project root as a whole no longer matches).
+fn whole_project_drift() -> DriftEvent {
+    DriftEvent { path: ".".to_string(), change: DriftChange::Modified, detected_at: Utc::now() }
+}
+
+fn is_change(kind: &EventKind) -> bool {
+    matches!(kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) | EventKind::Any)
+}
+
+/// Re-checks changed paths against the sealed lock manifest.
+struct DriftCheck {
+    root: PathBuf,
+    options: IdentityOptions,
+    filter: ScanFilter,
+    baseline: LockManifest,
+    entries: HashMap<String, LockEntry>,
+}
+
+impl DriftCheck {
+    fn new(root: PathBuf, options: IdentityOptions, baseline: LockManifest) -> Self {
+        let filter = ScanFilter::new(&root, &options);
+        let entries = baseline.files.iter().map(|entry| (entry.path.clone(), entry.clone())).collect();
+        DriftCheck { root, options, filter, baseline, entries }
+    }
+
+    /// Sealed files among `paths` whose leaf changed. Sealed files are re-hashed one by one;
+    /// any other path the scan would seal (new file, moved directory) triggers a full rescan
+    /// compared against the baseline.
+    fn check_paths(&self, paths: &BTreeSet<PathBuf>) -> anyhow::Result<Vec<DriftEvent>> {
+        let mut events = Vec::new();
+        let mut rescan = false;
+        for path in paths {
+            let Ok(relative) = path.strip_prefix(&self.root) else { continue };
+            if relative.as_os_str().is_empty() {
+                continue;
+            }
+            let leaf_path = self.options.canonical.path(relative);
+            match self.entries.get(&leaf_path) {
+                Some(entry) => {
+                    if let Some(change) = self.recheck(path, entry)? {
+                        events.push(DriftEvent { path: leaf_path, change, detected_at: Utc::now() });
+                    }
+                }
+                // Moved-in directories are reported as one event, so an unknown path means a rescan
+                // unless the scan leaves it out (excluded, ignored). New mutable files add a leaf too.
+                None if self.filter.is_pruned(relative)? => {}
+                None => rescan = true,
+            }
+        }
+        if rescan && events.is_empty() {
+            events = self.rescan()?;
+        }
+        Ok(events)
+    }
+
+    /// Re-hashes the whole project and lists every file that differs from the baseline.
+    fn rescan(&self) -> anyhow::Result<Vec<DriftEvent>> {
+        let current = compute_lock_manifest_with_options(&self.root, &self.options)?;
+        let diff = diff_manifests(&self.baseline, &current);
+        let detected_at = Utc::now();
+        let changes = diff.added.into_iter().map(|path| (path, DriftChange::Added))
+            .chain(diff.removed.into_iter().map(|path| (path, DriftChange::Removed)))
+            .chain(diff.modified.into_iter().map(|path| (path, DriftChange::Modified)))
+            .chain(diff.mutability_changed.into_iter().map(|change| (change.path, DriftChange::Modified)));
+        let mut events: Vec<DriftEvent> = changes.map(|(path, change)| DriftEvent { path, change, detected_at }).collect();
+        if events.is_empty() && current.root_hash != self.baseline.root_hash {
+            events.push(whole_project_drift());
+        }
+        Ok(events)
+    }
+
+    /// Whether the file still produces its sealed leaf. Mutable files may change, but not disappear.
+    fn recheck(&self, path: &Path, entry: &LockEntry) -> anyhow::Result<Option<DriftChange>> {
+        let Ok(link_metadata) = fs::symlink_metadata(path) else { return Ok(Some(DriftChange::Removed)) };
+        if entry.mutable {
+            return Ok(None);
+        }
+
+        let sealed_meta = entry.meta();
+        let (metadata, file_hash) = if sealed_meta.kind == merkle::FileKind::Symlink {
+            if !link_metadata.file_type().is_symlink() {
+                return Ok(Some(DriftChange::Modified));
+            }
+            (link_metadata, symlink::symlink_target_hash(&fs::read_link(path)?))
+        } else {
+            let metadata = if self.options.symlinks == SymlinkPolicy::Follow { fs::metadata(path)? } else { link_metadata };
+            if !metadata.is_file() {
+                return Ok(Some(DriftChange::Modified));
+            }
+            (metadata, self.options.canonical.file_hash(path)?)
+        };
+
+        let leaf = merkle::leaf_hash(self.baseline.scheme, &entry.path, &LeafMeta::from_metadata(&metadata), &file_hash);
+        Ok((leaf != entry.leaf).then_some(DriftChange::Modified))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_changed_sealed_files_are_reported() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let root = fs::canonicalize(dir.path())?;
+        fs::write(root.join("package.json"), "{}")?;
+        fs::write(root.join("server.js"), "v1")?;
+        fs::write(root.join("state.db"), "rows")?;
+        fs::write(root.join(".openseal_mutable"), "*.db\n")?;
+        let options = IdentityOptions::detect(&root);
+        let check = DriftCheck::new(root.clone(), options.clone(), compute_lock_manifest_with_options(&root, &options)?);
+        let changed = |paths: &[&str]| -> anyhow::Result<Vec<(String, DriftChange)>> {
+            let paths = paths.iter().map(|path| root.join(path)).collect();
+            Ok(check.check_paths(&paths)?.into_iter().map(|event| (event.path, event.change)).collect())
+        };
+
+        // Rewriting the same bytes, mutable content and excluded directories are not drift
+        fs::write(root.join("server.js"), "v1")?;
+        fs::write(root.join("state.db"), "more rows")?;
+        fs::create_dir_all(root.join("node_modules/dep"))?;
+        fs::write(root.join("node_modules/dep/index.js"), "x")?;
+        assert!(changed(&["server.js", "state.db", "node_modules/dep/index.js"])?.is_empty());
+
+        fs::write(root.join("server.js"), "v2")?;
+        assert_eq!(changed(&["server.js"])?, vec![("server.js".to_string(), DriftChange::Modified)]);
+        fs::write(root.join("server.js"), "v1")?;
+
+        fs::write(root.join("backdoor.js"), "evil")?;
+        assert_eq!(changed(&["backdoor.js"])?, vec![("backdoor.js".to_string(), DriftChange::Added)]);
+        fs::remove_file(root.join("backdoor.js"))?;
+
+        fs::remove_file(root.join("state.db"))?;
+        assert_eq!(changed(&["state.db"])?, vec![("state.db".to_string(), DriftChange::Removed)]);
+        assert_eq!(check.rescan()?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reattestation_needs_a_running_watcher() {
+        let monitor = IntegrityMonitor::new(Utc::now());
+        assert!(!monitor.request_reattestation());
+        let alive = WatchGuard::start(Arc::clone(&monitor));
+        assert!(monitor.request_reattestation());
+        drop(alive);
+        assert!(!monitor.is_watching() && !monitor.request_reattestation());
+    }
+
+    #[test]
+    fn test_only_paths_left_out_of_the_scan_skip_the_rescan() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let root = fs::canonicalize(dir.path())?;
+        fs::write(root.join("package.json"), "{}")?;
+        fs::write(root.join("server.js"), "v1")?;
+        fs::write(root.join(".ignore"), "logs/\n*.tmp\n")?;
+        fs::write(root.join(".openseal_mutable"), "*.db\n")?;
+        let options = IdentityOptions::detect(&root);
+        let check = DriftCheck::new(root.clone(), options.clone(), compute_lock_manifest_with_options(&root, &options)?);
+
+        fs::create_dir_all(root.join("logs"))?;
+        fs::create_dir_all(root.join(".git"))?;
+        fs::create_dir_all(root.join("data"))?;
+        for path in ["logs/app.log", ".git/index", "server.js.tmp", "data/cache.db", "backdoor.js"] {
+            fs::write(root.join(path), "x")?;
+        }
+        for path in ["logs", "logs/app.log", ".git/index", "server.js.tmp"] {
+            assert!(check.filter.is_pruned(Path::new(path))?, "{}", path);
+            assert!(check.check_paths(&BTreeSet::from([root.join(path)]))?.is_empty(), "{}", path);
+        }
+        for path in ["backdoor.js", "data", "data/cache.db"] {
+            assert!(!check.filter.is_pruned(Path::new(path))?, "{}", path);
+        }
+
+        // A new mutable file still adds a leaf: drift, like any other new file
+        fs::remove_file(root.join("backdoor.js"))?;
+        let changed = check.check_paths(&BTreeSet::from([root.join("data/cache.db")]))?;
+        assert_eq!(changed.len(), 1);
+        assert_eq!((changed[0].path.as_str(), changed[0].change), ("data/cache.db", DriftChange::Added));
+        Ok(())
+    }
+}